@@ -0,0 +1,108 @@
+use std::{fs, path::PathBuf, time::{SystemTime, UNIX_EPOCH}};
+
+use eframe::epaint::ahash::HashMap;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+use crate::{
+    ratings::{get_rating, set_ratings},
+    requests::get_community_annotations,
+    locale::t,
+    settings::{get_settings, is_offline, save, SETTINGS},
+    tags::{add_tags, get_tags},
+    util::GD_FOLDER,
+};
+
+lazy_static!{
+    // raw copy of the last fetched file
+    pub static ref ANNOTATIONS_CACHE_FILE: PathBuf = GD_FOLDER.join("gdsfx_community_annotations.json");
+}
+
+// [{ "id": 123, "tags": ["ambient", "loop"], "rating": 4 }]
+#[derive(Debug, Clone, Deserialize)]
+pub struct Annotation {
+    pub id: i64,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub rating: Option<u8>,
+}
+
+// what importing would change, user tags and ratings are never replaced
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnnotationDiff {
+    pub tags: Vec<(i64, String)>,
+    pub ratings: Vec<(i64, u8)>,
+}
+
+impl AnnotationDiff {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.ratings.is_empty()
+    }
+
+    pub fn apply(&self) {
+        let mut by_tag = HashMap::<&str, Vec<i64>>::default();
+        for (id, tag) in &self.tags {
+            by_tag.entry(tag).or_default().push(*id);
+        }
+        for (tag, ids) in by_tag {
+            add_tags(&ids, tag);
+        }
+        set_ratings(&self.ratings);
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// uses the cached file until the refetch interval has passed, blocks while downloading
+pub fn load_annotations() -> Result<Vec<Annotation>, String> {
+    let settings = get_settings();
+    if settings.community_annotations_url.trim().is_empty() {
+        return Err(t!("annotations.no_url").to_string());
+    }
+    let interval = settings.annotations_refetch_hours.saturating_mul(60 * 60);
+    // a cache of another source is never used, not even offline
+    let has_cache = ANNOTATIONS_CACHE_FILE.exists() && settings.last_annotations_url == settings.community_annotations_url;
+    let cache_is_fresh = has_cache && now() < settings.last_annotations_fetch.saturating_add(interval);
+
+    let data = if cache_is_fresh || (has_cache && is_offline()) {
+        fs::read_to_string(ANNOTATIONS_CACHE_FILE.as_path()).map_err(|e| e.to_string())?
+    } else {
+        let data = get_community_annotations(&settings.community_annotations_url)
+            .ok_or_else(|| format!("Couldn't download {}", settings.community_annotations_url))?;
+        fs::write(ANNOTATIONS_CACHE_FILE.as_path(), &data).map_err(|e| e.to_string())?;
+        let mut saved = SETTINGS.lock().unwrap();
+        saved.last_annotations_fetch = now();
+        saved.last_annotations_url = settings.community_annotations_url.clone();
+        drop(saved);
+        save();
+        data
+    };
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+pub fn annotation_diff(annotations: &[Annotation]) -> AnnotationDiff {
+    let mut diff = AnnotationDiff::default();
+    for annotation in annotations {
+        let existing = get_tags(annotation.id);
+        for tag in &annotation.tags {
+            let tag = tag.trim();
+            let is_new = !tag.is_empty()
+                && !existing.iter().any(|t| t == tag)
+                && !diff.tags.iter().any(|(id, t)| *id == annotation.id && t == tag);
+            if is_new {
+                diff.tags.push((annotation.id, tag.to_string()));
+            }
+        }
+        if let Some(rating) = annotation.rating {
+            if get_rating(annotation.id).is_none() && !diff.ratings.iter().any(|(id, _)| *id == annotation.id) {
+                diff.ratings.push((annotation.id, rating));
+            }
+        }
+    }
+    diff
+}
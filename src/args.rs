@@ -0,0 +1,32 @@
+use std::{env, path::PathBuf};
+
+use lazy_static::lazy_static;
+
+lazy_static!{
+    pub static ref ARGS: Args = Args::parse();
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Args {
+    pub offline: bool,
+    pub library_file: Option<PathBuf>, // --library <path>, used instead of the cdn
+    pub safe_mode: bool, // no cache, no network and default settings, for when the app won't start
+    pub debug: bool, // shows raw file and library data in the details
+}
+
+impl Args {
+    fn parse() -> Self {
+        let mut args = Args::default();
+        let mut iter = env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--offline" => args.offline = true,
+                "--library" => args.library_file = iter.next().map(PathBuf::from),
+                "--safe-mode" => args.safe_mode = true,
+                "--debug" => args.debug = true,
+                _ => {}
+            }
+        }
+        args
+    }
+}
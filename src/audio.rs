@@ -1,45 +1,273 @@
-use std::{io::Cursor, thread::{spawn, JoinHandle}, time::Instant, sync::Arc};
+use std::{io::Cursor, thread::{spawn, JoinHandle}, time::{Duration, Instant}, sync::Arc};
 
-use crossbeam_channel::{unbounded, Sender, Receiver};
-use eframe::epaint::mutex::Mutex;
+use crossbeam_channel::{bounded, unbounded, Sender, Receiver};
+use eframe::epaint::{ahash::{HashMap, HashSet}, mutex::Mutex};
 use lazy_static::lazy_static;
-use rodio::{OutputStream, Sink, Decoder};
+use rodio::{source::SineWave, OutputStream, Sink, Decoder, Source};
 
-use crate::library::LibraryEntry;
+use crate::{
+    encoding::is_ogg,
+    library::LibraryEntry,
+    locale::t,
+    notifications::push_error,
+    play_history::record_play,
+    requests::CDN_URL,
+    settings::{get_settings, is_offline},
+    stats::DOWNLOADS_IN_PROGRESS,
+    util::LOCAL_SFX_LIBRARY,
+};
+
+pub const TEST_BEEP_FREQUENCY: f32 = 440.0;
+pub const TEST_BEEP_DURATION: Duration = Duration::from_millis(500);
+pub const MAX_CONCURRENT_ANALYSES: usize = 2;
 
 lazy_static!{
     pub static ref PLAYERS: Arc<Mutex<usize>> = Default::default();
     pub static ref AUDIO_MESSAGES: (Sender<Instant>, Receiver<Instant>) = unbounded();
+    // the sound that was started last, until it stops or finishes
+    pub static ref NOW_PLAYING: Arc<Mutex<Option<i64>>> = Default::default();
+    // sound id -> probe result, None if the file couldn't be decoded
+    pub static ref AUDIO_INFO: Arc<Mutex<HashMap<i64, Option<AudioInfo>>>> = Default::default();
+    pub static ref ANALYSIS_QUEUE: Arc<Mutex<AnalysisQueue>> = Default::default();
+    // ids of sounds whose analysis just finished, the result is in AUDIO_INFO
+    pub static ref ANALYSIS_FINISHED: (Sender<i64>, Receiver<i64>) = unbounded();
+}
+
+// analyses run on a few worker threads so browsing quickly doesn't stall the gui
+#[derive(Debug, Default)]
+pub struct AnalysisQueue {
+    pending: Vec<LibraryEntry>, // newest request last
+    running: HashSet<i64>,
+    workers: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioInfo {
+    pub format: &'static str,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bit_depth: u16,
+}
+
+pub fn probe_audio(data: &[u8]) -> Option<AudioInfo> {
+    let decoder = Decoder::new(Cursor::new(data.to_vec())).ok()?;
+    Some(AudioInfo {
+        format: if is_ogg(data) { "Ogg Vorbis" } else { "Unknown" },
+        sample_rate: decoder.sample_rate(),
+        channels: decoder.channels(),
+        bit_depth: 16, // rodio always decodes to i16 samples
+    })
+}
+
+// the file has to be downloaded or already loaded into memory
+pub fn has_audio_data(sfx: &LibraryEntry) -> bool {
+    sfx.exists() || LOCAL_SFX_LIBRARY.lock().contains_key(&sfx.id())
+}
+
+// the start of the sound file, without downloading it
+pub fn audio_head(sfx: &LibraryEntry, len: usize) -> Option<Vec<u8>> {
+    if !has_audio_data(sfx) {
+        return None;
+    }
+    let mut data = sfx.download(CDN_URL)?;
+    data.truncate(len);
+    Some(data)
+}
+
+pub fn clear_audio_info() {
+    AUDIO_INFO.lock().clear();
+}
+
+// None until the analysis has finished, see is_analysing
+pub fn audio_info(sfx: &LibraryEntry) -> Option<AudioInfo> {
+    if !has_audio_data(sfx) {
+        return None;
+    }
+    if let Some(info) = AUDIO_INFO.lock().get(&sfx.id()) {
+        return *info;
+    }
+    request_analysis(sfx);
+    None
+}
+
+pub fn is_analysing(id: i64) -> bool {
+    let queue = ANALYSIS_QUEUE.lock();
+    queue.running.contains(&id) || queue.pending.iter().any(|sfx| sfx.id() == id)
+}
+
+pub fn analyses_in_progress() -> bool {
+    ANALYSIS_QUEUE.lock().workers > 0
+}
+
+// analyses that finished since the last call, polled by the gui every frame
+pub fn finished_analyses() -> Vec<i64> {
+    ANALYSIS_FINISHED.1.try_iter().collect()
+}
+
+// drops queued analyses for sounds that aren't shown anymore, running ones still finish
+pub fn cancel_analyses_except(id: i64) {
+    ANALYSIS_QUEUE.lock().pending.retain(|sfx| sfx.id() == id);
+}
+
+fn request_analysis(sfx: &LibraryEntry) {
+    let mut queue = ANALYSIS_QUEUE.lock();
+    if queue.running.contains(&sfx.id()) {
+        return;
+    }
+    // requesting the same sound again only moves it to the front
+    queue.pending.retain(|pending| pending.id() != sfx.id());
+    queue.pending.push(sfx.clone());
+    if queue.workers < MAX_CONCURRENT_ANALYSES {
+        queue.workers += 1;
+        spawn(analysis_worker);
+    }
 }
 
+fn analysis_worker() {
+    loop {
+        let mut queue = ANALYSIS_QUEUE.lock();
+        let Some(sfx) = queue.pending.pop() else {
+            queue.workers -= 1;
+            return;
+        };
+        queue.running.insert(sfx.id());
+        drop(queue);
+
+        let info = sfx.download(CDN_URL).and_then(|data| probe_audio(&data));
+        AUDIO_INFO.lock().insert(sfx.id(), info);
+        ANALYSIS_QUEUE.lock().running.remove(&sfx.id());
+        ANALYSIS_FINISHED.0.send(sfx.id()).unwrap();
+    }
+}
+
+fn open_output() -> Result<(OutputStream, Sink), String> {
+    let (stream, handle) = OutputStream::try_default().map_err(|e| e.to_string())?;
+    let sink = Sink::try_new(&handle).map_err(|e| e.to_string())?;
+    Ok((stream, sink))
+}
+
+// hover previews skip this, so only deliberate plays end up in the play history
 pub fn play_sound(sfx: &LibraryEntry, cdn_url: &str) {
+    if play_sound_with_volume(sfx, cdn_url, 1.0) {
+        record_play(sfx);
+    }
+}
+
+// false if the sound couldn't be loaded
+pub fn play_sound_with_volume(sfx: &LibraryEntry, cdn_url: &str, volume: f32) -> bool {
     let data = sfx.download(cdn_url);
     if let Some(content) = data {
-        play_ogg(content);
+        play_ogg(sfx.id(), content, volume);
+        return true;
+    }
+    if is_offline() {
+        push_error(t!("error.offline_not_downloaded", name = sfx.name()));
+    } else {
+        push_error(t!("error.download", name = sfx.name()));
     }
+    false
+}
+
+// repeats (start, end) in centiseconds until stopped, for finding a seamless loop
+pub fn play_loop(sfx: &LibraryEntry, cdn_url: &str, (start, end): (i64, i64)) -> bool {
+    let Some(content) = sfx.download(cdn_url) else {
+        push_error(t!("error.download", name = sfx.name()));
+        return false;
+    };
+    let region = (Duration::from_millis(start as u64 * 10), Duration::from_millis((end - start) as u64 * 10));
+    spawn_player(sfx.id(), content, 1.0, Some(region));
+    true
+}
+
+pub fn download_and_play(sfx: &LibraryEntry) {
+    let sfx = sfx.clone();
+    DOWNLOADS_IN_PROGRESS.lock().unwrap().insert(sfx.id());
+    spawn(move || {
+        sfx.download_and_store();
+        DOWNLOADS_IN_PROGRESS.lock().unwrap().remove(&sfx.id());
+        if sfx.exists() {
+            stop_audio();
+            play_sound(&sfx, CDN_URL);
+        } else {
+            push_error(t!("error.download", name = sfx.name()));
+        }
+    });
 }
 
-pub fn play_ogg(ogg: Vec<u8>) -> JoinHandle<()> {
-    spawn(|| {
+pub fn play_ogg(id: i64, ogg: Vec<u8>, volume: f32) -> JoinHandle<()> {
+    spawn_player(id, ogg, volume, None)
+}
+
+// the loop region is (start, length) and replaces the preview length
+fn spawn_player(id: i64, ogg: Vec<u8>, volume: f32, loop_region: Option<(Duration, Duration)>) -> JoinHandle<()> {
+    spawn(move || {
         *PLAYERS.lock() += 1;
+        *NOW_PLAYING.lock() = Some(id);
         let start_time = Instant::now();
         let cursor = Cursor::new(ogg);
-        let (_stream, handle) = OutputStream::try_default().unwrap();
-        let sink = Sink::try_new(&handle).unwrap();
-        sink.append(Decoder::new(cursor).unwrap());
-        while !sink.empty() {
-            if let Ok(received_time) = AUDIO_MESSAGES.1.try_recv() {
-                if received_time > start_time {
-                    sink.stop();
+        if let Ok((_stream, sink)) = open_output() {
+            sink.set_volume(volume);
+            let source = Decoder::new(cursor).unwrap();
+            match (loop_region, get_settings().preview_length_secs) {
+                (Some((start, length)), _) => {
+                    sink.append(source.skip_duration(start).take_duration(length).repeat_infinite())
+                }
+                (None, Some(secs)) => sink.append(source.take_duration(Duration::from_secs_f32(secs))),
+                (None, None) => sink.append(source),
+            }
+            while !sink.empty() {
+                if let Ok(received_time) = AUDIO_MESSAGES.1.try_recv() {
+                    if received_time > start_time {
+                        sink.stop();
+                    }
                 }
             }
         }
         *PLAYERS.lock() -= 1;
+
+        // a newer sound may have taken over already
+        let mut now_playing = NOW_PLAYING.lock();
+        if *now_playing == Some(id) && *PLAYERS.lock() == 0 {
+            *now_playing = None;
+        }
     })
 }
 
+pub fn now_playing() -> Option<i64> {
+    *NOW_PLAYING.lock()
+}
+
+pub fn is_playing() -> bool {
+    *PLAYERS.lock() > 0
+}
+
 pub fn stop_audio() {
     for _ in 0..*PLAYERS.lock() {
         AUDIO_MESSAGES.0.send(Instant::now()).unwrap();
     }
 }
+
+// plays a short built-in beep, independent of the CDN
+// returns the output device error if audio can't be played
+pub fn test_audio() -> Result<(), String> {
+    let (sender, receiver) = bounded(1);
+    spawn(move || {
+        match open_output() {
+            Ok((_stream, sink)) => {
+                let _ = sender.send(Ok(()));
+                sink.append(
+                    SineWave::new(TEST_BEEP_FREQUENCY)
+                        .take_duration(TEST_BEEP_DURATION)
+                        .amplify(0.2),
+                );
+                sink.sleep_until_end();
+            }
+            Err(error) => {
+                let _ = sender.send(Err(error));
+            }
+        }
+    });
+    receiver
+        .recv()
+        .unwrap_or_else(|_| Err("Audio thread stopped unexpectedly".to_string()))
+}
@@ -0,0 +1,54 @@
+use std::{sync::{atomic::{AtomicBool, Ordering}, Mutex}, thread::{sleep, spawn}, time::Duration};
+
+use crate::{bandwidth, favourites, hidden, loop_regions, notes, ratings, tags, util::SHUTTING_DOWN};
+
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(3);
+
+// changes only mark the data as dirty, the autosave thread writes it
+// at most once per AUTOSAVE_INTERVAL so bulk edits don't hit the disk every time
+pub struct Autosave {
+    dirty: AtomicBool,
+    saving: Mutex<()>,
+    save: fn(),
+}
+
+impl Autosave {
+    pub const fn new(save: fn()) -> Self {
+        Autosave {
+            dirty: AtomicBool::new(false),
+            saving: Mutex::new(()),
+            save,
+        }
+    }
+
+    pub fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    pub fn flush(&self) {
+        // the exit flush may run while the thread is still writing
+        let _lock = self.saving.lock().unwrap();
+        if self.dirty.swap(false, Ordering::Relaxed) {
+            (self.save)();
+        }
+    }
+}
+
+pub fn flush_all() {
+    favourites::AUTOSAVE.flush();
+    notes::AUTOSAVE.flush();
+    tags::AUTOSAVE.flush();
+    ratings::AUTOSAVE.flush();
+    loop_regions::AUTOSAVE.flush();
+    hidden::AUTOSAVE.flush();
+    bandwidth::AUTOSAVE.flush();
+}
+
+pub fn start_autosave() {
+    spawn(|| {
+        while !SHUTTING_DOWN.load(Ordering::Relaxed) {
+            sleep(AUTOSAVE_INTERVAL);
+            flush_all();
+        }
+    });
+}
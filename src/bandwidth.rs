@@ -0,0 +1,60 @@
+use std::{path::PathBuf, sync::{atomic::{AtomicU64, Ordering}, Arc, Mutex}, fs};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::{autosave::Autosave, util::GD_FOLDER, encoding::{full_decode, full_encode}};
+
+lazy_static!{
+    pub static ref BANDWIDTH_FILE: PathBuf = GD_FOLDER.join("gdsfx_bandwidth.dat");
+    pub static ref BANDWIDTH: Arc<Mutex<Bandwidth>> = Arc::new(Mutex::new(read_file()));
+}
+
+pub static AUTOSAVE: Autosave = Autosave::new(save);
+
+// starts at 0 on every launch
+static SESSION_BYTES: AtomicU64 = AtomicU64::new(0);
+
+// all-time totals
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bandwidth {
+    pub total_bytes: u64,
+    pub sessions: u64,
+}
+
+pub fn read_file() -> Bandwidth {
+    if BANDWIDTH_FILE.exists() {
+        let data = fs::read(BANDWIDTH_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        Bandwidth::default()
+    }
+}
+
+pub fn save() {
+    let string = serde_json::to_string(&*BANDWIDTH.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(BANDWIDTH_FILE.as_path(), data).unwrap();
+}
+
+pub fn start_session() {
+    BANDWIDTH.lock().unwrap().sessions += 1;
+    AUTOSAVE.mark_dirty();
+}
+
+// called with the size of every successful download from the cdn
+pub fn record_download(bytes: usize) {
+    SESSION_BYTES.fetch_add(bytes as u64, Ordering::Relaxed);
+    BANDWIDTH.lock().unwrap().total_bytes += bytes as u64;
+    AUTOSAVE.mark_dirty();
+}
+
+pub fn session_bytes() -> u64 {
+    SESSION_BYTES.load(Ordering::Relaxed)
+}
+
+pub fn get_bandwidth() -> Bandwidth {
+    BANDWIDTH.lock().unwrap().clone()
+}
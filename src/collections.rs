@@ -0,0 +1,104 @@
+use std::{path::PathBuf, sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex}, fs};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::{util::GD_FOLDER, encoding::{full_decode, full_encode}};
+
+lazy_static!{
+    pub static ref COLLECTIONS_FILE: PathBuf = GD_FOLDER.join("gdsfx_collections.dat");
+    pub static ref COLLECTIONS: Arc<Mutex<Vec<Collection>>> = Arc::new(Mutex::new(read_file()));
+}
+
+// bumped on every change so cached search results filtering by collection get refreshed
+static COLLECTIONS_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Collection {
+    pub name: String,
+    pub description: String,
+    pub entries: Vec<i64>,
+}
+
+pub fn read_file() -> Vec<Collection> {
+    if COLLECTIONS_FILE.exists() {
+        let data = fs::read(COLLECTIONS_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save() {
+    let string = serde_json::to_string(&*COLLECTIONS.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(COLLECTIONS_FILE.as_path(), data).unwrap();
+}
+
+pub fn get_collections() -> Vec<Collection> {
+    COLLECTIONS.lock().unwrap().clone()
+}
+
+pub fn collection_contains(name: &str, id: i64) -> bool {
+    COLLECTIONS
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|collection| collection.name == name && collection.entries.contains(&id))
+}
+
+// changes whenever sounds are added to or removed from a collection
+fn collections_changed() {
+    COLLECTIONS_GENERATION.fetch_add(1, Ordering::Relaxed);
+    save();
+}
+
+pub fn collections_generation() -> usize {
+    COLLECTIONS_GENERATION.load(Ordering::Relaxed)
+}
+
+pub fn add_collection(name: &str) -> usize {
+    let mut collections = COLLECTIONS.lock().unwrap();
+    collections.push(Collection {
+        name: name.to_string(),
+        ..Default::default()
+    });
+    let index = collections.len() - 1;
+    drop(collections);
+    collections_changed();
+    index
+}
+
+pub fn remove_collection(index: usize) {
+    let mut collections = COLLECTIONS.lock().unwrap();
+    if index < collections.len() {
+        collections.remove(index);
+    }
+    drop(collections);
+    collections_changed();
+}
+
+pub fn set_collection_description(index: usize, description: &str) {
+    if let Some(collection) = COLLECTIONS.lock().unwrap().get_mut(index) {
+        collection.description = description.to_string();
+    }
+    collections_changed();
+}
+
+pub fn add_to_collection(index: usize, id: i64) {
+    if let Some(collection) = COLLECTIONS.lock().unwrap().get_mut(index) {
+        if !collection.entries.contains(&id) {
+            collection.entries.push(id);
+        }
+    }
+    collections_changed();
+}
+
+pub fn remove_from_collection(index: usize, id: i64) {
+    if let Some(collection) = COLLECTIONS.lock().unwrap().get_mut(index) {
+        collection.entries.retain(|entry| *entry != id);
+    }
+    collections_changed();
+}
@@ -0,0 +1,87 @@
+use std::{path::PathBuf, sync::{Arc, Mutex}, fs};
+
+use eframe::epaint::Color32;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{filters::{fold_case, name_matches}, library::LibraryEntry, tags::get_tags, util::GD_FOLDER, encoding::{full_decode, full_encode}};
+
+lazy_static!{
+    pub static ref COLOR_RULES_FILE: PathBuf = GD_FOLDER.join("gdsfx_color_rules.dat");
+    pub static ref COLOR_RULES: Arc<Mutex<Vec<ColorRule>>> = Arc::new(Mutex::new(read_file()));
+}
+
+pub const TAG_PREFIX: &str = "tag:";
+
+// pattern is one of:
+// `tag:<tag>` - sounds with that tag
+// `<min>-<max>` - sounds with an id in that range (inclusive)
+// anything else - sounds whose name contains it, ignoring case
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorRule {
+    pub pattern: String,
+    #[serde(serialize_with = "serialize_color", deserialize_with = "deserialize_color")]
+    pub color: Color32,
+}
+
+impl ColorRule {
+    pub fn matches(&self, sound: &LibraryEntry) -> bool {
+        let pattern = self.pattern.trim();
+        if pattern.is_empty() {
+            return false;
+        }
+        if let Some(tag) = pattern.strip_prefix(TAG_PREFIX) {
+            return get_tags(sound.id()).iter().any(|t| t.eq_ignore_ascii_case(tag.trim()));
+        }
+        if let Some((min, max)) = pattern.split_once('-') {
+            if let (Ok(min), Ok(max)) = (min.trim().parse::<i64>(), max.trim().parse::<i64>()) {
+                return (min..=max).contains(&sound.id());
+            }
+        }
+        name_matches(sound.name(), &fold_case(pattern))
+    }
+}
+
+pub fn serialize_color<S: Serializer>(color: &Color32, serializer: S) -> Result<S::Ok, S::Error> {
+    color.to_array().serialize(serializer)
+}
+
+pub fn deserialize_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color32, D::Error> {
+    let [r, g, b, a] = <[u8; 4]>::deserialize(deserializer)?;
+    Ok(Color32::from_rgba_premultiplied(r, g, b, a))
+}
+
+pub fn read_file() -> Vec<ColorRule> {
+    if COLOR_RULES_FILE.exists() {
+        let data = fs::read(COLOR_RULES_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save() {
+    let string = serde_json::to_string(&*COLOR_RULES.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(COLOR_RULES_FILE.as_path(), data).unwrap();
+}
+
+pub fn get_color_rules() -> Vec<ColorRule> {
+    COLOR_RULES.lock().unwrap().clone()
+}
+
+pub fn set_color_rules(rules: Vec<ColorRule>) {
+    *COLOR_RULES.lock().unwrap() = rules;
+    save();
+}
+
+// color of the first matching rule
+pub fn sound_color(sound: &LibraryEntry) -> Option<Color32> {
+    COLOR_RULES
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|rule| rule.matches(sound))
+        .map(|rule| rule.color)
+}
@@ -0,0 +1,90 @@
+use std::{fs, panic, path::PathBuf, sync::{Arc, Mutex}, thread};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::{failed_downloads::retry_failed_downloads, gui::{GdSfx, Sorting}, stats::DOWNLOADS_IN_PROGRESS, util::GD_FOLDER};
+
+lazy_static!{
+    pub static ref CRASH_RECOVERY_FILE: PathBuf = GD_FOLDER.join("crash_recovery.json");
+    // the panic hook can't reach the app, so the state is copied here every frame
+    pub static ref RECOVERY_STATE: Arc<Mutex<RecoveryState>> = Default::default();
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecoveryState {
+    pub search_query: String,
+    pub sorting: Sorting,
+    pub selected_sfx: Option<i64>,
+    // sounds queued by category downloads and retries, failed downloads are saved on their own
+    pub downloads: Vec<i64>,
+}
+
+impl RecoveryState {
+    pub fn of(gdsfx: &GdSfx) -> Self {
+        RecoveryState {
+            search_query: gdsfx.search_query.clone(),
+            sorting: gdsfx.sorting,
+            selected_sfx: gdsfx.selected_sfx.as_ref().map(|sfx| sfx.id()),
+            downloads: DOWNLOADS_IN_PROGRESS.lock().unwrap().iter().copied().collect(),
+        }
+    }
+
+    pub fn restore(self, gdsfx: &mut GdSfx) {
+        gdsfx.search_query = self.search_query;
+        gdsfx.sorting = self.sorting;
+        if let Some(id) = self.selected_sfx {
+            let sfx = gdsfx.sfx_library.as_ref().and_then(|library| library.find_by_id(id)).cloned();
+            if sfx.is_some() {
+                gdsfx.selected_sfx = sfx;
+                gdsfx.scroll_to_sfx = Some(id);
+            }
+        }
+        if let Some(library) = gdsfx.sfx_library.as_ref() {
+            let sounds = self
+                .downloads
+                .iter()
+                .filter_map(|id| library.find_by_id(*id))
+                .filter(|sound| sound.is_sound() && !sound.exists())
+                .cloned()
+                .collect();
+            // the retry queue downloads them one at a time in the background
+            retry_failed_downloads(sounds);
+        }
+    }
+}
+
+// saves the last state before the panic message is printed,
+// background threads can panic without taking the app down so only the ui thread counts.
+// called on the thread that runs the gui, thread names aren't the same on every platform
+pub fn install_panic_hook() {
+    let ui_thread = thread::current().id();
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let is_ui_thread = thread::current().id() == ui_thread;
+        // the panic might have happened while the state was locked
+        if let (true, Ok(state)) = (is_ui_thread, RECOVERY_STATE.try_lock()) {
+            if let Ok(string) = serde_json::to_string(&*state) {
+                let _ = fs::write(CRASH_RECOVERY_FILE.as_path(), string);
+            }
+        }
+        default_hook(info);
+    }));
+}
+
+pub fn update_recovery_state(gdsfx: &GdSfx) {
+    *RECOVERY_STATE.lock().unwrap() = RecoveryState::of(gdsfx);
+}
+
+// a clean exit never leaves a state to restore
+pub fn remove_recovery_file() {
+    let _ = fs::remove_file(CRASH_RECOVERY_FILE.as_path());
+}
+
+// the file is removed right away, a dismissed state isn't offered again
+pub fn take_recovery_state() -> Option<RecoveryState> {
+    let data = fs::read(CRASH_RECOVERY_FILE.as_path()).ok()?;
+    remove_recovery_file();
+    serde_json::from_slice(&data).ok()
+}
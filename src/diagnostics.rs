@@ -0,0 +1,42 @@
+use std::{env, fmt::Write};
+
+use crate::{
+    gui::LibraryVersion,
+    notifications::recent_errors,
+    settings::{is_offline, SETTINGS_FILE},
+    stats::EXISTING_SOUND_FILES,
+    util::GD_FOLDER,
+};
+
+// paths contain the user name, so the home directory is replaced with `~`
+fn redact(text: &str) -> String {
+    ["HOME", "USERPROFILE", "localappdata"]
+        .into_iter()
+        .filter_map(|var| env::var(var).ok())
+        .filter(|dir| !dir.is_empty())
+        .fold(text.to_string(), |text, dir| text.replace(&dir, "~"))
+}
+
+// plain-text report for bug reports
+pub fn diagnostics_report(sfx_version: Option<LibraryVersion>, cdn_url: Option<&str>) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "gd_sfx v{}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "OS: {} ({})", env::consts::OS, env::consts::ARCH);
+    let _ = writeln!(
+        report,
+        "Library version: {}",
+        sfx_version.map_or("unknown".to_string(), |version| version.to_string())
+    );
+    let _ = writeln!(report, "CDN: {}", cdn_url.unwrap_or("none"));
+    let _ = writeln!(report, "Offline: {}", is_offline());
+    let _ = writeln!(report, "Downloaded files: {}", EXISTING_SOUND_FILES.lock().unwrap().len());
+    let _ = writeln!(report, "Data folder: {}", redact(&GD_FOLDER.to_string_lossy()));
+    let _ = writeln!(report, "Settings file: {}", redact(&SETTINGS_FILE.to_string_lossy()));
+
+    let errors = recent_errors();
+    let _ = writeln!(report, "Recent errors ({}):", errors.len());
+    for error in errors {
+        let _ = writeln!(report, "- {}", redact(&error));
+    }
+    report
+}
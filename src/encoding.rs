@@ -1,6 +1,9 @@
-use std::io::prelude::*;
+use std::io::{prelude::*, Cursor};
 use base64::prelude::*;
 use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use rodio::{Decoder, Source};
+
+pub const OGG_MAGIC: &[u8] = b"OggS";
 
 pub fn base64_decode(data: &[u8]) -> Vec<u8> {
     BASE64_URL_SAFE.decode(data).unwrap()
@@ -30,7 +33,32 @@ pub fn full_decode(data: &[u8]) -> Vec<u8> {
     zlib_decode(&data)
 }
 
+// like full_decode, but for data that might not be valid
+pub fn try_full_decode(data: &[u8]) -> Result<Vec<u8>, String> {
+    let data = BASE64_URL_SAFE.decode(data).map_err(|e| format!("invalid base64: {e}"))?;
+    let mut output = Vec::with_capacity(data.len() * 2);
+    ZlibDecoder::new(data.as_slice())
+        .read_to_end(&mut output)
+        .map_err(|e| format!("invalid zlib data: {e}"))?;
+    Ok(output)
+}
+
 pub fn full_encode(data: &[u8]) -> String {
     let data = zlib_encode(data);
     base64_encode(&data)
 }
+
+pub fn is_ogg(data: &[u8]) -> bool {
+    data.starts_with(OGG_MAGIC)
+}
+
+// decodes the whole file to count samples, vorbis streams don't always report their length
+pub fn ogg_duration(data: &[u8]) -> Option<i64> {
+    let decoder = Decoder::new(Cursor::new(data.to_vec())).ok()?;
+    let samples_per_second = decoder.channels() as u64 * decoder.sample_rate() as u64;
+    if samples_per_second == 0 {
+        return None;
+    }
+    let samples = decoder.count() as u64;
+    Some((samples * 100 / samples_per_second) as i64) // in centiseconds
+}
@@ -0,0 +1,216 @@
+use std::{fmt::Write, fs, io, path::Path};
+
+use eframe::epaint::ahash::{HashMap, HashSet};
+use pretty_bytes::converter::convert;
+use serde_json::json;
+
+use crate::{collections::Collection, library::{CategoryEntry, Library, LibraryEntry}, play_history::PlayRecord};
+
+// GD level string conventions used for sfx triggers:
+// objects are `key,value` pairs separated by commas and terminated by `;`,
+// `1` is the object id, `2`/`3` are the x/y position and `392` is the sfx id
+pub const SFX_TRIGGER_OBJECT_ID: i64 = 3602;
+pub const KEY_OBJECT_ID: u32 = 1;
+pub const KEY_X: u32 = 2;
+pub const KEY_Y: u32 = 3;
+pub const KEY_SFX_ID: u32 = 392;
+pub const TRIGGER_Y: f64 = 15.0; // first row above the ground
+// units travelled per second at normal (1x) speed, used to space out the triggers
+pub const NORMAL_SPEED_UNITS_PER_SECOND: f64 = 311.58;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OutlineOptions {
+    pub include_ids: bool,
+    pub include_sizes: bool,
+}
+
+// indented plain-text outline of the category tree, like a directory listing
+pub fn library_outline(root: &LibraryEntry, options: OutlineOptions) -> String {
+    fn recurse(entry: &LibraryEntry, depth: usize, options: OutlineOptions, output: &mut String) {
+        let indent = "  ".repeat(depth);
+        match entry {
+            LibraryEntry::Category(CategoryEntry { children, .. }) => {
+                let _ = write!(output, "{indent}{}/", entry.name());
+                if options.include_ids {
+                    let _ = write!(output, " [#{}]", entry.id());
+                }
+                output.push('\n');
+
+                let (sounds, categories): (Vec<_>, Vec<_>) =
+                    children.iter().partition(|c| c.is_sound());
+                for child in categories.into_iter().chain(sounds) {
+                    recurse(child, depth + 1, options, output);
+                }
+            }
+            LibraryEntry::Sound(_) => {
+                let _ = write!(output, "{indent}{}", entry.name());
+                let details = [
+                    options.include_ids.then(|| format!("#{}", entry.id())),
+                    options.include_sizes.then(|| convert(entry.bytes() as f64)),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+                if !details.is_empty() {
+                    let _ = write!(output, " [{}]", details.join(", "));
+                }
+                output.push('\n');
+            }
+        }
+    }
+
+    let mut output = String::new();
+    // the root is named after the library version, so only its contents are listed
+    for child in root.children().into_iter().flatten() {
+        recurse(child, 0, options, &mut output);
+    }
+    output
+}
+
+pub fn export_library_outline(root: &LibraryEntry, options: OutlineOptions, path: &Path) -> io::Result<()> {
+    fs::write(path, library_outline(root, options))
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// one row per sound, the category path is joined with slashes like in the outline
+pub fn library_csv(root: &LibraryEntry) -> String {
+    let mut output = String::from("category,id,name,bytes,duration\n");
+    for (path, sound) in root.iter_with_path() {
+        let _ = writeln!(
+            output,
+            "{},{},{},{},{}",
+            csv_field(&path.join("/")),
+            sound.id(),
+            csv_field(sound.name()),
+            sound.bytes(),
+            sound.duration(),
+        );
+    }
+    output
+}
+
+pub fn export_library_csv(root: &LibraryEntry, path: &Path) -> io::Result<()> {
+    fs::write(path, library_csv(root))
+}
+
+pub fn sfx_trigger_object(sfx_id: i64, x: f64) -> String {
+    format!(
+        "{KEY_OBJECT_ID},{SFX_TRIGGER_OBJECT_ID},{KEY_X},{x:.2},{KEY_Y},{TRIGGER_Y},{KEY_SFX_ID},{sfx_id};"
+    )
+}
+
+// sfx ids in pasted text, level objects are read by their sfx id key so positions
+// and other numbers in them aren't mistaken for ids, anything else counts every number
+pub fn sfx_ids_in_text(text: &str) -> Vec<i64> {
+    let key = KEY_SFX_ID.to_string();
+    let in_objects = text
+        .split(';')
+        .flat_map(|object| {
+            let fields = object.trim().split(',').collect::<Vec<_>>();
+            fields
+                .chunks_exact(2)
+                .filter(|pair| pair[0] == key)
+                .filter_map(|pair| pair[1].parse().ok())
+                .collect::<Vec<i64>>()
+        })
+        .collect::<Vec<_>>();
+    let mut ids = if in_objects.is_empty() {
+        text.split(|c: char| !c.is_ascii_digit())
+            .filter_map(|number| number.parse().ok())
+            .collect()
+    } else {
+        in_objects
+    };
+    let mut seen = HashSet::default();
+    ids.retain(|id| seen.insert(*id));
+    ids
+}
+
+// a single sfx trigger at the origin, in the level string object format:
+// comma separated key,value pairs terminated by a semicolon, e.g. `1,3602,2,0.00,3,15,392,<sfx id>;`
+// gd doesn't read the system clipboard, so this is meant for level string editors
+pub fn sfx_trigger_snippet(sound: &LibraryEntry) -> String {
+    sfx_trigger_object(sound.id(), 0.0)
+}
+
+// one sfx trigger per sound, placed so each one starts when the previous sound ends
+pub fn collection_trigger_script(collection: &Collection, library: &Library) -> String {
+    let mut comments = String::new();
+    let mut objects = String::new();
+    let mut x = 0.0;
+
+    let _ = writeln!(comments, "# Collection: {}", collection.name);
+    for (i, sound) in collection.entries.iter().filter_map(|id| library.find_by_id(*id)).enumerate() {
+        let _ = writeln!(comments, "# {}. {} (ID: {}) at x = {x:.2}", i + 1, sound.name(), sound.id());
+        objects.push_str(&sfx_trigger_object(sound.id(), x));
+        x += sound.duration() as f64 / 100.0 * NORMAL_SPEED_UNITS_PER_SECOND;
+    }
+
+    format!("{comments}{objects}\n")
+}
+
+pub fn export_collection_trigger_script(collection: &Collection, library: &Library, path: &Path) -> io::Result<()> {
+    fs::write(path, collection_trigger_script(collection, library))
+}
+
+// `2024-01-15T12:34:56Z`
+pub fn iso8601_utc(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let secs = timestamp % 86400;
+    // days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+// summary fields followed by every play, oldest first
+pub fn play_history_json(history: &[PlayRecord]) -> String {
+    let mut counts = HashMap::<i64, usize>::default();
+    for record in history {
+        *counts.entry(record.id).or_default() += 1;
+    }
+    let most_played = counts
+        .iter()
+        .max_by_key(|(id, count)| (**count, -**id))
+        .and_then(|(id, count)| {
+            let record = history.iter().find(|record| record.id == *id)?;
+            Some(json!({ "id": id, "name": record.name, "plays": count }))
+        });
+    let plays = history
+        .iter()
+        .map(|record| {
+            json!({ "id": record.id, "name": record.name, "played_at": iso8601_utc(record.played_at) })
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::to_string_pretty(&json!({
+        "total_plays": history.len(),
+        "unique_sounds_played": counts.len(),
+        "most_played_sound": most_played,
+        "plays": plays,
+    }))
+    .unwrap()
+}
+
+pub fn export_play_history(history: &[PlayRecord], path: &Path) -> io::Result<()> {
+    fs::write(path, play_history_json(history))
+}
@@ -0,0 +1,124 @@
+use std::{path::PathBuf, sync::{atomic::Ordering, Arc, Mutex}, fs, thread::{sleep, spawn}, time::Duration};
+
+use lazy_static::lazy_static;
+
+use crate::{
+    library::LibraryEntry,
+    locale::t,
+    notifications::{push_error, push_notification},
+    stats::DOWNLOADS_IN_PROGRESS,
+    util::{GD_FOLDER, SHUTTING_DOWN},
+    encoding::{full_decode, full_encode},
+};
+
+pub const RETRY_ATTEMPTS: u32 = 3;
+pub const RETRY_BASE_DELAY: Duration = Duration::from_secs(1); // doubled after every failed attempt
+pub const RETRY_INTERVAL: Duration = Duration::from_millis(250); // between sounds, to go easy on the cdn
+
+lazy_static!{
+    pub static ref FAILED_DOWNLOADS_FILE: PathBuf = GD_FOLDER.join("gdsfx_failed_downloads.dat");
+    pub static ref FAILED_DOWNLOADS: Arc<Mutex<Vec<i64>>> = Arc::new(Mutex::new(read_file()));
+    // (done, total) while retrying
+    pub static ref RETRY_PROGRESS: Arc<Mutex<Option<(usize, usize)>>> = Default::default();
+}
+
+pub fn read_file() -> Vec<i64> {
+    if FAILED_DOWNLOADS_FILE.exists() {
+        let data = fs::read(FAILED_DOWNLOADS_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save() {
+    let string = serde_json::to_string(&*FAILED_DOWNLOADS.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(FAILED_DOWNLOADS_FILE.as_path(), data).unwrap();
+}
+
+pub fn get_failed_downloads() -> Vec<i64> {
+    FAILED_DOWNLOADS.lock().unwrap().clone()
+}
+
+pub fn add_failed_download(id: i64) {
+    let mut failed = FAILED_DOWNLOADS.lock().unwrap();
+    if failed.contains(&id) {
+        return;
+    }
+    failed.push(id);
+    drop(failed);
+    save();
+}
+
+pub fn remove_failed_download(id: i64) {
+    let mut failed = FAILED_DOWNLOADS.lock().unwrap();
+    if !failed.contains(&id) {
+        return;
+    }
+    failed.retain(|failed| *failed != id);
+    drop(failed);
+    save();
+}
+
+pub fn clear_failed_downloads() {
+    FAILED_DOWNLOADS.lock().unwrap().clear();
+    save();
+}
+
+pub fn retry_progress() -> Option<(usize, usize)> {
+    *RETRY_PROGRESS.lock().unwrap()
+}
+
+// one sound at a time, each with exponential backoff between attempts
+pub fn retry_failed_downloads(sounds: Vec<LibraryEntry>) {
+    {
+        let mut progress = RETRY_PROGRESS.lock().unwrap();
+        if progress.is_some() || sounds.is_empty() {
+            return;
+        }
+        *progress = Some((0, sounds.len()));
+    }
+    DOWNLOADS_IN_PROGRESS.lock().unwrap().extend(sounds.iter().map(|sound| sound.id()));
+
+    spawn(move || {
+        let mut still_failing = 0;
+        for sound in &sounds {
+            let mut delay = RETRY_BASE_DELAY;
+            for attempt in 0..RETRY_ATTEMPTS {
+                if SHUTTING_DOWN.load(Ordering::Relaxed) || sound.exists() {
+                    break;
+                }
+                if attempt > 0 {
+                    sleep(delay);
+                    delay *= 2;
+                }
+                sound.download_and_store(); // removes the sound from the queue on success
+            }
+            if !sound.exists() {
+                still_failing += 1;
+            }
+            DOWNLOADS_IN_PROGRESS.lock().unwrap().remove(&sound.id());
+            if let Some((done, _)) = RETRY_PROGRESS.lock().unwrap().as_mut() {
+                *done += 1;
+            }
+            if SHUTTING_DOWN.load(Ordering::Relaxed) {
+                break;
+            }
+            sleep(RETRY_INTERVAL);
+        }
+        let mut in_progress = DOWNLOADS_IN_PROGRESS.lock().unwrap();
+        for sound in &sounds {
+            in_progress.remove(&sound.id());
+        }
+        drop(in_progress);
+        *RETRY_PROGRESS.lock().unwrap() = None;
+
+        if still_failing > 0 {
+            push_error(t!("failed_downloads.still_failing", count = still_failing));
+        } else {
+            push_notification(t!("failed_downloads.all_retried", count = sounds.len()));
+        }
+    });
+}
@@ -1,9 +1,9 @@
-use std::{path::PathBuf, sync::{Arc, Mutex}, fs};
+use std::{path::PathBuf, sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex}, fs};
 
 use eframe::epaint::ahash::HashSet;
 use lazy_static::lazy_static;
 
-use crate::{util::GD_FOLDER, encoding::{zlib_encode, base64_encode, full_decode, full_encode}};
+use crate::{autosave::Autosave, util::GD_FOLDER, encoding::{zlib_encode, base64_encode, full_decode, full_encode}};
 
 lazy_static!{
     pub static ref FAVOURITES_FILE: PathBuf = GD_FOLDER.join("gdsfx_favourites.dat");
@@ -14,6 +14,11 @@ lazy_static!{
 
 pub const FAVOURITES_CHARACTER: char = '⭐';
 
+pub static AUTOSAVE: Autosave = Autosave::new(save);
+
+// bumped on every change so cached search results showing only favourites get refreshed
+static FAVOURITES_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
 pub fn read_file() -> HashSet<i64> {
     if FAVOURITES_FILE.exists() {
         let mut favourites = HashSet::default();
@@ -43,9 +48,23 @@ pub fn save() {
     fs::write(FAVOURITES_FILE.as_path(), data).unwrap();
 }
 
+fn favourites_changed() {
+    FAVOURITES_GENERATION.fetch_add(1, Ordering::Relaxed);
+    AUTOSAVE.mark_dirty();
+}
+
+pub fn favourites_generation() -> usize {
+    FAVOURITES_GENERATION.load(Ordering::Relaxed)
+}
+
 pub fn add_favourite(id: i64) {
     FAVOURITES_LIST.lock().unwrap().insert(id);
-    save();
+    favourites_changed();
+}
+
+pub fn add_favourites(ids: &[i64]) {
+    FAVOURITES_LIST.lock().unwrap().extend(ids);
+    favourites_changed();
 }
 
 pub fn has_favourite(id: i64) -> bool {
@@ -54,5 +73,5 @@ pub fn has_favourite(id: i64) -> bool {
 
 pub fn remove_favourite(id: i64) {
     FAVOURITES_LIST.lock().unwrap().remove(&id);
-    save();
+    favourites_changed();
 }
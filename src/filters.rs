@@ -0,0 +1,194 @@
+use eframe::epaint::ahash::HashMap;
+use serde::{Deserialize, Serialize};
+use unicode_casefold::UnicodeCaseFold;
+
+use crate::{collections::collection_contains, hidden::is_hidden, library::LibraryEntry, settings::SETTINGS, tags::get_tags};
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Filters {
+    pub favourites_only: bool,
+    pub downloaded_only: bool,
+    pub min_duration: Option<f64>, // in seconds
+    pub max_duration: Option<f64>, // in seconds
+    pub min_size: Option<f64>,     // in kB
+    pub max_size: Option<f64>,     // in kB
+    pub categories: Vec<i64>,      // sounds directly in these categories, see filter_sounds for subcategories
+    pub tag: Option<String>,
+    pub collection: Option<String>, // by name, so presets work with other people's collections
+    pub show_hidden: bool, // hidden categories are skipped in filter_sounds
+    pub ids: Vec<i64>, // e.g. pasted from a level
+}
+
+impl Filters {
+    pub fn is_empty(&self) -> bool {
+        *self == Filters::default()
+    }
+
+    pub fn matches(&self, sound: &LibraryEntry) -> bool {
+        let duration = sound.duration() as f64 / 100.0;
+        let size = sound.bytes() as f64 / 1000.0;
+
+        (self.categories.is_empty() || self.categories.contains(&sound.parent()))
+            && (!self.favourites_only || sound.is_favourite())
+            && (!self.downloaded_only || sound.exists())
+            && self.min_duration.is_none_or(|min| duration >= min)
+            && self.max_duration.is_none_or(|max| duration <= max)
+            && self.min_size.is_none_or(|min| size >= min)
+            && self.max_size.is_none_or(|max| size <= max)
+            && self.tag.as_ref().is_none_or(|tag| get_tags(sound.id()).contains(tag))
+            && self.collection.as_ref().is_none_or(|name| collection_contains(name, sound.id()))
+            && (self.show_hidden || !is_hidden(sound.id()))
+            && (self.ids.is_empty() || self.ids.contains(&sound.id()))
+    }
+}
+
+// unicode-aware replacement for to_ascii_lowercase when comparing names
+pub fn fold_case(text: &str) -> String {
+    text.chars().case_fold().collect()
+}
+
+// `query` has to be case folded already
+pub fn name_matches(name: &str, query: &str) -> bool {
+    if fold_case(name).contains(query) {
+        return true;
+    }
+    // lets e.g. kana names be found by typing romaji
+    SETTINGS.lock().unwrap().transliterate_search
+        && fold_case(&deunicode::deunicode(name)).contains(query)
+}
+
+// case folded names by sound id, built once per library so a search doesn't fold every name again
+#[derive(Debug, Default, Clone)]
+pub struct NameIndex {
+    // (folded name, folded transliteration if it's any different)
+    names: HashMap<i64, (String, Option<String>)>,
+}
+
+impl NameIndex {
+    pub fn build(root: &LibraryEntry) -> Self {
+        let names = root
+            .sounds()
+            .into_iter()
+            .map(|sound| {
+                let folded = fold_case(sound.name());
+                let transliterated = fold_case(&deunicode::deunicode(sound.name()));
+                let transliterated = (transliterated != folded).then_some(transliterated);
+                (sound.id(), (folded, transliterated))
+            })
+            .collect();
+        NameIndex { names }
+    }
+
+    fn candidates(&self, id: i64, transliterate: bool) -> Option<impl Iterator<Item = &str>> {
+        let (folded, transliterated) = self.names.get(&id)?;
+        let transliterated = transliterated.as_deref().filter(|_| transliterate);
+        Some(std::iter::once(folded.as_str()).chain(transliterated))
+    }
+
+    // same as name_matches, sounds missing from the index fall back to it
+    pub fn matches(&self, sound: &LibraryEntry, query: &str, transliterate: bool) -> bool {
+        match self.candidates(sound.id(), transliterate) {
+            Some(mut names) => names.any(|name| name.contains(query)),
+            None => name_matches(sound.name(), query),
+        }
+    }
+
+    // same as name_similarity
+    pub fn similarity(&self, sound: &LibraryEntry, query: &str, transliterate: bool) -> f64 {
+        let Some(names) = self.candidates(sound.id(), transliterate) else {
+            return name_similarity(sound.name(), query);
+        };
+        if self.matches(sound, query, transliterate) {
+            return 1.0;
+        }
+        names
+            .flat_map(|name| std::iter::once(name).chain(name.split_whitespace()))
+            .map(|candidate| strsim::jaro_winkler(query, candidate))
+            .fold(0.0, f64::max)
+    }
+}
+
+// best jaro-winkler score of the query against the whole name or any of its words,
+// substring matches always score 1. `query` has to be case folded already
+pub fn name_similarity(name: &str, query: &str) -> f64 {
+    if name_matches(name, query) {
+        return 1.0;
+    }
+    let mut names = vec![fold_case(name)];
+    if SETTINGS.lock().unwrap().transliterate_search {
+        names.push(fold_case(&deunicode::deunicode(name)));
+    }
+    names
+        .iter()
+        .flat_map(|name| std::iter::once(name.as_str()).chain(name.split_whitespace()))
+        .map(|candidate| strsim::jaro_winkler(query, candidate))
+        .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use crate::library::{CategoryEntry, LibraryEntry, SoundEntry};
+
+    use super::{name_matches, NameIndex};
+
+    const SOUNDS: i64 = 5000;
+    const QUERIES: [&str; 5] = ["loop", "ambience 0", "strasse", "impact 12", "zzz"]; // already case folded
+    const ROUNDS: usize = 20;
+
+    fn synthetic_library() -> LibraryEntry {
+        let words = ["Background", "Ambience", "Loop", "Impact", "STRASSE", "Whoosh", "Glitch", "Hit"];
+        let children = (0..SOUNDS)
+            .map(|id| {
+                LibraryEntry::Sound(SoundEntry {
+                    id: id + 2,
+                    name: format!("{} {} {:02}", words[id as usize % words.len()], words[id as usize / 7 % words.len()], id % 100),
+                    parent: 1,
+                    bytes: 0,
+                    duration: 0,
+                    original_index: 0,
+                })
+            })
+            .collect();
+        LibraryEntry::Category(CategoryEntry {
+            id: 1,
+            name: "1".to_string(),
+            parent: 0,
+            children,
+            original_index: 0,
+        })
+    }
+
+    // cargo test --release -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn name_index_is_faster_than_folding_every_name() {
+        let root = synthetic_library();
+        let index = NameIndex::build(&root);
+        let sounds = root.sounds();
+
+        let start = Instant::now();
+        let mut expected = Vec::new();
+        for _ in 0..ROUNDS {
+            for query in QUERIES {
+                expected.push(sounds.iter().filter(|sound| name_matches(sound.name(), query)).count());
+            }
+        }
+        let folding = start.elapsed();
+
+        let start = Instant::now();
+        let mut actual = Vec::new();
+        for _ in 0..ROUNDS {
+            for query in QUERIES {
+                actual.push(sounds.iter().filter(|sound| index.matches(sound, query, false)).count());
+            }
+        }
+        let indexed = start.elapsed();
+
+        println!("{} searches over {SOUNDS} sounds: name_matches {folding:?}, NameIndex {indexed:?}", ROUNDS * QUERIES.len());
+        assert_eq!(expected, actual);
+        assert!(indexed < folding);
+    }
+}
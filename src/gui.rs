@@ -1,31 +1,274 @@
+use std::{fs, thread::spawn, time::{Duration, Instant}};
+
+use eframe::epaint::ahash::{HashMap, HashSet};
+
+use crossbeam_channel::{unbounded, Receiver};
 use eframe::{
-    egui::{self, Button, Ui},
+    egui::{self, collapsing_header::CollapsingState, Button, Ui},
     NativeOptions,
 };
+use egui_modal::Modal;
 use pretty_bytes::converter::convert;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
 use strum::{EnumIter, IntoEnumIterator};
 
 use crate::{
-    audio::{play_sound, stop_audio},
-    favourites::{add_favourite, has_favourite, remove_favourite},
-    library::{Library, LibraryEntry},
-    requests::CDN_URL,
-    stats::EXISTING_SOUND_FILES,
-    util::stringify_duration,
+    args::ARGS,
+    autosave::flush_all,
+    bandwidth::{get_bandwidth, session_bytes},
+    annotations::{annotation_diff, load_annotations, AnnotationDiff},
+    audio::{
+        analyses_in_progress, audio_head, audio_info, cancel_analyses_except, clear_audio_info, download_and_play,
+        finished_analyses, has_audio_data, is_analysing, is_playing, now_playing, play_sound,
+        play_loop, play_sound_with_volume, stop_audio, test_audio,
+    },
+    color_rules::{get_color_rules, set_color_rules, sound_color, ColorRule, TAG_PREFIX},
+    collections::{
+        self, add_collection, add_to_collection, collections_generation, get_collections, remove_collection,
+        remove_from_collection, set_collection_description,
+    },
+    favourites::{
+        self, add_favourite, add_favourites, favourites_generation, has_favourite, remove_favourite,
+        FAVOURITES_CHARACTER,
+    },
+    filters::{fold_case, name_matches, name_similarity, Filters, NameIndex},
+    crash_recovery::{remove_recovery_file, take_recovery_state, update_recovery_state, RecoveryState},
+    diagnostics::diagnostics_report,
+    encoding::{is_ogg, ogg_duration},
+    export::{
+        export_collection_trigger_script, export_library_csv, sfx_ids_in_text, export_library_outline, export_play_history,
+        sfx_trigger_snippet, OutlineOptions,
+    },
+    failed_downloads::{
+        clear_failed_downloads, get_failed_downloads, retry_failed_downloads, retry_progress,
+    },
+    play_history::get_play_history,
+    hidden::{get_hidden, hidden_generation, hide, is_hidden, unhide},
+    presets::{load_preset, save_preset, ViewPreset},
+    ratings::{get_rating, set_rating, MAX_RATING},
+    loop_regions::{get_loop_region, set_loop_region, MIN_LOOP_LENGTH},
+    search_history::{get_search_history, record_search},
+    keybindings::KeyBindings,
+    locale::{install_fallback_fonts, t, Locale},
+    library::{
+        remove_partial_downloads, CategoryEntry, Library, LibraryEntry, SoundEntry, CUSTOM_CATEGORY_ID, NO_ORIGINAL_INDEX,
+        RECENTLY_ADDED_CATEGORY_ID, RECENTLY_ADDED_CATEGORY_NAME,
+    },
+    library_history::{added_since, library_version, record_library},
+    requests::{sfx_url, using_cached_library, CDN_URL},
+    notes::{get_category_note, set_category_note},
+    notifications::{active_notifications, push_error, push_notification},
+    settings::{
+        self, categories_only, get_settings, is_offline, is_pinned, is_safe_mode, leave_safe_mode,
+        pin_category, set_settings, unpin_category,
+    },
+    stats::{
+        cache_history, category_progress, category_download_counts, check_all_sfx_files, delete_category, download_category, is_downloading,
+        delete_orphaned_files, orphaned_files, orphaned_size, outdated_sounds, redownload_progress, redownload_sounds,
+        sound_files_generation, EXISTING_SOUND_FILES,
+    },
+    tags::{
+        add_tag, add_tags, all_tags, delete_tag, get_tags, palette_tags, remove_tag, remove_tags,
+        rename_tag, set_palette, tags_generation, PaletteTag, DEFAULT_TAG_COLOR,
+    },
+    theme::{current_theme, AppTheme},
+    updates::{check_for_updates, AVAILABLE_UPDATE},
+    usage::{self, get_levels, is_used, mark_used, unmark_used, usage_stats, USED_CHARACTER},
+    util::{
+        acquire_instance_lock, clear_sound_cache, hex_dump, sound_cache_size, stringify_duration, GD_FOLDER, LIBRARY_WIDTH, LOCAL_SFX_LIBRARY, RIGHT_PANEL_WIDTH, SFX_LIBRARY_FILE, SHUTTING_DOWN,
+        TOTAL_HEIGHT, TOTAL_WIDTH,
+    },
 };
 
-pub type VersionType = usize;
+pub type SearchScores = HashMap<i64, f64>; // id -> similarity to the query, categories hold their best sound's
+pub type SearchResults = (Vec<LibraryEntry>, SearchScores);
+pub type LibraryLoad = (Option<String>, Option<LibraryVersion>, Option<Library>); // (cdn url, version, library)
+
+// transparent so the library history file keeps storing plain numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LibraryVersion(pub usize);
+
+impl std::fmt::Display for LibraryVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v{}", self.0)
+    }
+}
+
+impl TryFrom<&str> for LibraryVersion {
+    type Error = String;
+
+    // accepts "42", "v42" and surrounding whitespace
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        let trimmed = text.trim();
+        let number = trimmed.strip_prefix(['v', 'V']).unwrap_or(trimmed);
+        number
+            .parse()
+            .map(LibraryVersion)
+            .map_err(|_| format!("Invalid library version: {text:?}"))
+    }
+}
+
+impl TryFrom<String> for LibraryVersion {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        LibraryVersion::try_from(text.as_str())
+    }
+}
+
+pub const WINDOW_TITLE: &str = "GDSFX";
+pub const PLAYING_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+pub const JUMP_INDEX_WIDTH: f32 = 16.0;
+pub const MAX_ANNOTATIONS_REFETCH_HOURS: u64 = 24 * 365;
+pub const JUMP_INDEX_OTHER: char = '#';
+pub const HOVER_PREVIEW_VOLUME: f32 = 0.5;
+pub const AUTO_PLAY_DEBOUNCE: Duration = Duration::from_millis(250);
+pub const BULK_CONFIRM_THRESHOLD: usize = 50;
+pub const FULLSCREEN_FONT_SCALE: f32 = 1.25;
+pub const SEARCH_SUGGESTIONS: usize = 3;
+pub const AUTOCOMPLETE_SUGGESTIONS: usize = 10;
+pub const DEFAULT_FUZZINESS: f32 = 0.2;
+pub const MAX_FUZZINESS: f32 = 0.6;
 
 #[derive(Debug, Default, Clone)]
 pub struct GdSfx {
     pub cdn_url: Option<String>,
-    pub sfx_version: Option<VersionType>,
+    pub sfx_version: Option<LibraryVersion>,
     pub sfx_library: Option<Library>,
 
     pub stage: Stage,
+    pub scrolled_to_stage: Option<Stage>,
     pub search_query: String,
+    pub credits_query: String, // kept apart so it doesn't filter the library
     pub sorting: Sorting,
+    pub filters: Filters,
     pub selected_sfx: Option<LibraryEntry>,
+
+    pub audio_test_result: Option<Result<(), String>>,
+    pub library_reload: Option<Receiver<LibraryLoad>>,
+    pub started_loading: bool, // the library loads after the window opens
+
+    pub last_version_check: Option<Instant>,
+    pub version_check: Option<Receiver<Option<LibraryVersion>>>,
+    pub new_library_version: Option<LibraryVersion>,
+
+    // (id, hover start, has started playing)
+    pub hover_preview: Option<(i64, Instant, bool)>,
+    pub hovering_sound: bool,
+    // first letter -> offset of the first row starting with it, from the last drawn library list
+    pub jump_offsets: HashMap<char, f32>,
+    pub jump_to: Option<f32>,
+    // while drawing the recently added category, its sounds are copies of ones further down
+    pub in_virtual_category: bool,
+
+    pub custom_sounds: Vec<LibraryEntry>,
+
+    pub outline_options: OutlineOptions,
+    pub outline_path: Option<String>,
+    pub csv_path: Option<String>,
+    pub preset_path: Option<String>,
+    pub play_history_path: Option<String>,
+
+    // (sound to favourite, its already favourited duplicates)
+    pub pending_favourite: Option<(LibraryEntry, Vec<LibraryEntry>)>,
+
+    pub new_collection_name: String,
+
+    pub category_open: HashMap<i64, bool>,
+    // open states from before focusing a category
+    pub unfocused_category_open: Option<HashMap<i64, bool>>,
+
+    pub scroll_to_sfx: Option<i64>,
+
+    // (sound, sounds similar to it)
+    pub similar_sounds: Option<(LibraryEntry, Vec<LibraryEntry>)>,
+
+    pub recently_added: HashSet<i64>,
+
+    // (id, selection time)
+    pub pending_auto_play: Option<(i64, Instant)>,
+
+    // (sound, level name being typed)
+    pub usage_dialog: Option<(LibraryEntry, String)>,
+
+    pub bulk_tag: String,
+    pub pending_bulk_action: Option<BulkAction>,
+    pub new_tag: String,
+
+    pub key_bindings: KeyBindings,
+    pub show_help: bool,
+
+    pub window_size: Option<egui::Vec2>,
+    pub fullscreen: bool,
+
+    pub another_instance_running: bool,
+    pub recovery_state: Option<RecoveryState>,
+
+    // only the retry and settings ui is shown until a library loads
+    pub safe_mode: bool,
+    pub applied_theme: Option<AppTheme>,
+    pub title_playing: bool,
+    pub last_interaction: Option<Instant>,
+    pub caches_trimmed: bool, // by trim_when_idle
+
+    pub search_results: Option<(SearchKey, Vec<LibraryEntry>)>,
+    pub search_scores: SearchScores, // only filled in fuzzy mode
+    pub result_counts: (usize, usize), // (shown, downloaded), counted when the results arrive
+    pub pending_search: Option<(SearchKey, Receiver<SearchResults>)>,
+    pub fuzziness: Option<f32>, // None = exact search
+    pub match_categories: bool, // a matching category keeps all of its sounds
+    // (query, suggestions with whether they come from the search history)
+    pub autocomplete: Option<(String, Vec<(String, bool)>)>,
+    pub autocomplete_index: Option<usize>, // suggestion highlighted with the arrow keys
+    pub multi_select: HashSet<i64>, // toggled with ctrl + click
+    pub pasted_ids: Option<(String, Vec<i64>)>, // (query before the paste, sound ids in the pasted text)
+    // (ids that got the tag, tag) of the last tagging, for undo
+    pub tag_undo: Option<(Vec<i64>, String)>,
+
+    pub cache_history: Option<Vec<(u64, usize, u64)>>, // read once when the stats are shown
+    pub outdated_sounds: Option<Vec<LibraryEntry>>, // None until checked
+    pub orphaned_files: Option<(usize, Vec<i64>, u64)>, // (file generation, ids, total bytes), sizing them needs the disk
+    pub confirm_orphan_cleanup: bool,
+    pub confirm_redownload: bool,
+    pub confirm_category_download: Option<(i64, bool)>, // (category id, re-download existing files)
+
+    pub annotations_fetch: Option<Receiver<Result<AnnotationDiff, String>>>,
+    pub annotations_preview: Option<AnnotationDiff>,
+
+    // drag and drop between the sound list and the tag palette
+    pub dragged_sound: Option<LibraryEntry>,
+    pub dragged_tag: Option<String>,
+    pub tag_rename: Option<(String, String)>, // (old name, new name)
+    pub new_palette_tag: String,
+    // categories whose sounds are shown anyway in categories only mode
+    pub revealed_categories: HashSet<i64>,
+    pub search_suggestions: Option<(String, Vec<String>)>, // (query, suggestions)
+    pub go_to_id: Option<(String, Option<String>)>, // (input, error)
+}
+
+// everything the filtered library depends on, results are recomputed when this changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchKey {
+    query: String,
+    filters: Filters,
+    library_version: Option<LibraryVersion>,
+    custom_sounds: usize, // only ever added to
+    favourites: usize, // generation
+    transliterate: bool,
+    downloaded: usize, // generation
+    min_similarity: Option<f64>,
+    tags: usize,
+    hidden: usize,
+    collections: usize, // generation
+    match_categories: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BulkAction {
+    Favourite(Vec<i64>),
+    Tag(Vec<i64>, String),
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumIter)]
@@ -33,11 +276,32 @@ pub enum Stage {
     #[default]
     Library,
     Favourites,
+    Collections,
     Stats,
+    Settings,
     Credits,
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+impl Stage {
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Library => t!("stage.library"),
+            Stage::Favourites => t!("stage.favourites"),
+            Stage::Collections => t!("stage.collections"),
+            Stage::Stats => t!("stage.stats"),
+            Stage::Settings => t!("stage.settings"),
+            Stage::Credits => t!("stage.credits"),
+        }
+    }
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
 pub enum Sorting {
     #[default]
     Default,
@@ -51,372 +315,3752 @@ pub enum Sorting {
     SizeDec,   // 9kb - 0kb
 }
 
-impl eframe::App for GdSfx {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        top_panel(ctx, self);
-        main_scroll_area(ctx, self);
-        side_bar_sfx(ctx, self.selected_sfx.as_ref());
-    }
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+pub enum DetailPanelPosition {
+    #[default]
+    Right,
+    Bottom,
+    Floating,
 }
 
-impl GdSfx {
-    pub fn run(self, options: NativeOptions) {
-        eframe::run_native("GDSFX", options, Box::new(|_cc| Box::new(self))).unwrap()
+impl DetailPanelPosition {
+    pub fn name(self) -> &'static str {
+        match self {
+            DetailPanelPosition::Right => t!("detail_position.right"),
+            DetailPanelPosition::Bottom => t!("detail_position.bottom"),
+            DetailPanelPosition::Floating => t!("detail_position.floating"),
+        }
     }
 }
 
-fn top_panel(ctx: &egui::Context, gdsfx: &mut GdSfx) {
-    egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-        ui.add_space(4.0);
-        ui.horizontal(|ui| {
-            Stage::iter().for_each(|stage| {
-                ui.selectable_value(&mut gdsfx.stage, stage, format!("{:?}", stage));
-            });
-        });
-        ui.add_space(2.0);
-    });
+// what clicking the sound that's currently playing does
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+pub enum ReplayBehavior {
+    #[default]
+    Restart,
+    Toggle, // stops it
+    Ignore,
 }
 
-fn main_scroll_area(ctx: &egui::Context, gdsfx: &mut GdSfx) {
-    egui::SidePanel::left("left_panel").show(ctx, |ui| {
-        /*
-        // reconsider these
-        if let Some(version) = gdsfx.sfx_version {
-            ui.heading(format!("Library version: {version}"));
+impl ReplayBehavior {
+    pub fn name(self) -> &'static str {
+        match self {
+            ReplayBehavior::Restart => t!("replay_behavior.restart"),
+            ReplayBehavior::Toggle => t!("replay_behavior.toggle"),
+            ReplayBehavior::Ignore => t!("replay_behavior.ignore"),
         }
-        if ui.button("Force-update library").clicked() {
-            gdsfx.get_sfx_library(true);
-        }
-        ui.separator();
-        */
+    }
+}
 
-        if let Stage::Library | Stage::Favourites = gdsfx.stage {
-            search_bar(ui, gdsfx);
-            sort_menu(ui, gdsfx);
-            ui.separator();
+// how search results are shown, without a search the library is always a tree
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+pub enum ResultLayout {
+    #[default]
+    Tree,
+    Flat,
+    Grouped, // one collapsible header per category with matches
+}
+
+impl ResultLayout {
+    pub fn name(self) -> &'static str {
+        match self {
+            ResultLayout::Tree => t!("result_layout.tree"),
+            ResultLayout::Flat => t!("result_layout.flat"),
+            ResultLayout::Grouped => t!("result_layout.grouped"),
         }
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            if let Some(sfx_library) = gdsfx.sfx_library.as_ref() {
-                match gdsfx.stage {
-                    Stage::Library => {
-                        let library = gdsfx.sfx_library.clone().unwrap().sound_effects;
-                        let mut sfx =
-                            filter_sounds(&library, &gdsfx.search_query.to_ascii_lowercase());
-                        if !sfx.is_empty() {
-                            remove_empty_category_nodes(&mut sfx[0]);
-                            library_list(ui, gdsfx, &sfx[0]);
-                        }
-                    }
-                    Stage::Favourites => {
-                        favourites_list(ui, gdsfx, sfx_library.sound_effects.clone())
-                    }
-                    Stage::Stats => stats_list(ui, gdsfx),
-                    Stage::Credits => credits_list(ui, gdsfx),
-                }
-            }
-        });
-    });
+    }
 }
 
-fn library_list(ui: &mut Ui, gdsfx: &mut GdSfx, sfx_library: &LibraryEntry) {
-    fn recursive(gdsfx: &mut GdSfx, entry: &LibraryEntry, ui: &mut egui::Ui) {
-        match entry {
-            LibraryEntry::Category { children, .. } => {
-                let (mut sounds, mut categories): (Vec<_>, Vec<_>) =
-                    children.iter().partition(|x| !x.is_category());
-
-                let sorting = |a: &&LibraryEntry, b: &&LibraryEntry| {
-                    match gdsfx.sorting {
-                        Sorting::Default => std::cmp::Ordering::Equal,
-                        Sorting::NameInc => a.name().cmp(b.name()),
-                        Sorting::NameDec => b.name().cmp(a.name()),
-                        Sorting::LengthInc => a.duration().cmp(&b.duration()),
-                        Sorting::LengthDec => b.duration().cmp(&a.duration()),
-                        Sorting::IdInc => b.id().cmp(&a.id()), // this is not a bug, in gd, the id sorting is reversed,
-                        Sorting::IdDec => a.id().cmp(&b.id()), // in-game it's `ID+ => 9 - 0; ID- => 0 - 9`
-                        Sorting::SizeInc => a.bytes().cmp(&b.bytes()),
-                        Sorting::SizeDec => b.bytes().cmp(&a.bytes()),
-                    }
-                };
+// what the metadata bar above the library list shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+pub enum MetadataItem {
+    Counts, // shown / total sounds
+    Downloaded,
+    Version,
+    Cdn,
+}
 
-                categories.sort_by(sorting);
-                sounds.sort_by(sorting);
+impl MetadataItem {
+    pub fn name(self) -> &'static str {
+        match self {
+            MetadataItem::Counts => t!("metadata_item.counts"),
+            MetadataItem::Downloaded => t!("metadata_item.downloaded"),
+            MetadataItem::Version => t!("metadata_item.version"),
+            MetadataItem::Cdn => t!("metadata_item.cdn"),
+        }
+    }
+}
 
-                if entry.parent() == 0 {
-                    // root
-                    for child in categories {
-                        recursive(gdsfx, child, ui);
-                    }
-                } else {
-                    let is_disabled = sounds.is_empty() && categories.is_empty(); // an empty query will always match everything
+// the labels gd uses for its sorting options
+impl std::fmt::Display for Sorting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Sorting::Default => "Default",
+            Sorting::NameInc => "Name+",
+            Sorting::NameDec => "Name-",
+            Sorting::LengthInc => "Length+",
+            Sorting::LengthDec => "Length-",
+            Sorting::IdInc => "ID+",
+            Sorting::IdDec => "ID-",
+            Sorting::SizeInc => "Size+",
+            Sorting::SizeDec => "Size-",
+        })
+    }
+}
 
-                    ui.add_enabled_ui(!is_disabled, |ui| {
-                        ui.collapsing(entry.name(), |ui| {
-                            for child in categories {
-                                recursive(gdsfx, child, ui);
-                            }
-                            for child in sounds {
-                                recursive(gdsfx, child, ui);
-                            }
-                        });
-                    });
-                }
-            }
-            LibraryEntry::Sound { .. } => {
-                sfx_button(ui, gdsfx, entry);
+impl eframe::App for GdSfx {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if !self.started_loading {
+            self.started_loading = true;
+            // safe mode starts without the library until the user retries
+            if !self.safe_mode {
+                self.load_library(false);
             }
         }
+        if ctx.input_mut(|i| i.consume_shortcut(&self.key_bindings.reload_library)) {
+            self.reload_library();
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&self.key_bindings.show_help)) {
+            self.show_help = !self.show_help;
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&self.key_bindings.toggle_fullscreen)) {
+            self.toggle_fullscreen(ctx);
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&self.key_bindings.go_to_id)) {
+            self.go_to_id = Some(Default::default());
+        }
+        // text fields keep their own undo
+        if ctx.memory(|mem| mem.focus().is_none())
+            && ctx.input_mut(|i| i.consume_shortcut(&self.key_bindings.undo_tags))
+        {
+            self.undo_tags();
+        }
+        let theme = current_theme();
+        if self.applied_theme != Some(theme) {
+            ctx.set_visuals(theme.visuals());
+            self.applied_theme = Some(theme);
+        }
+        self.poll_playback(ctx);
+        self.poll_analyses(ctx);
+        self.window_size = Some(ctx.screen_rect().size());
+        self.poll_library_reload(ctx);
+        self.poll_version_check(ctx);
+        self.import_dropped_files(ctx);
+        self.poll_annotations_fetch(ctx);
+
+        if self.safe_mode {
+            safe_mode_panel(ctx, self);
+            notifications_overlay(ctx);
+            return;
+        }
+
+        self.update_search(ctx);
+        update_recovery_state(self);
+        self.trim_when_idle(ctx);
+
+        top_panel(ctx, self);
+        // panels take their space in the order they're added
+        if get_settings().detail_panel_position == DetailPanelPosition::Bottom {
+            side_bar_sfx(ctx, self);
+            main_scroll_area(ctx, self);
+        } else {
+            main_scroll_area(ctx, self);
+            side_bar_sfx(ctx, self);
+        }
+        similar_sounds_window(ctx, self);
+        self.auto_play_selection(ctx);
+
+        duplicate_favourite_dialog(ctx, self);
+        usage_dialog(ctx, self);
+        bulk_action_dialog(ctx, self);
+        redownload_dialog(ctx, self);
+        orphan_cleanup_dialog(ctx, self);
+        category_download_dialog(ctx, self);
+        another_instance_dialog(ctx, self);
+        help_window(ctx, self);
+        go_to_id_window(ctx, self);
+        annotations_dialog(ctx, self);
+        notifications_overlay(ctx);
+        self.drag_preview(ctx);
     }
-    recursive(gdsfx, sfx_library, ui);
-}
 
-fn favourites_list(ui: &mut Ui, gdsfx: &mut GdSfx, sfx_library: LibraryEntry) {
-    fn recursive(gdsfx: &mut GdSfx, entry: &LibraryEntry, ui: &mut egui::Ui) {
-        match entry {
-            LibraryEntry::Category { children, .. } => {
-                for child in children {
-                    recursive(gdsfx, child, ui);
-                }
-            }
-            LibraryEntry::Sound { name, id, .. } => {
-                if has_favourite(*id)
-                    && name
-                        .to_ascii_lowercase()
-                        .contains(&gdsfx.search_query.to_ascii_lowercase())
-                {
-                    sfx_button(ui, gdsfx, entry)
-                }
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        SHUTTING_DOWN.store(true, std::sync::atomic::Ordering::Relaxed);
+        stop_audio();
+        flush_all();
+        collections::save();
+        usage::save();
+        // the defaults loaded in safe mode must not replace the saved settings
+        if !is_safe_mode() {
+            let mut settings = settings::SETTINGS.lock().unwrap();
+            // the fullscreen size would open a window covering the whole screen
+            if let Some(size) = self.window_size.filter(|_| !self.fullscreen) {
+                settings.last_window_size = Some((size.x, size.y));
             }
+            // closing before the library loaded keeps the sound to restore next time
+            if self.sfx_library.is_some() {
+                settings.last_selected_sfx = self.selected_sfx.as_ref().map(|sfx| sfx.id());
+            }
+            drop(settings);
+            settings::save();
         }
+        remove_partial_downloads();
+        remove_recovery_file();
     }
-    recursive(gdsfx, &sfx_library, ui);
 }
 
-fn stats_list(ui: &mut Ui, gdsfx: &mut GdSfx) {
-    // (bytes, duration, files)
-    fn recursive(entry: &LibraryEntry) -> (u128, u128, i64) {
-        match entry {
-            LibraryEntry::Category { children, .. } => children
-                .iter()
-                .map(recursive)
-                .reduce(|a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2))
-                .unwrap_or((0, 0, 1)),
-            LibraryEntry::Sound {
-                bytes, duration, ..
-            } => (*bytes as u128, *duration as u128, 1),
+impl GdSfx {
+    pub fn new() -> Self {
+        GdSfx {
+            sorting: get_settings().default_sorting,
+            another_instance_running: !acquire_instance_lock(),
+            recovery_state: take_recovery_state(),
+            safe_mode: is_safe_mode(),
+            applied_theme: None,
+            ..Default::default()
         }
     }
-    let (total_bytes, total_duration, total_files) =
-        recursive(&gdsfx.sfx_library.as_ref().unwrap().sound_effects);
 
-    ui.heading("SFX Library");
+    pub fn run(self, options: NativeOptions) {
+        eframe::run_native(
+            WINDOW_TITLE,
+            options,
+            Box::new(|cc| {
+                install_fallback_fonts(&cc.egui_ctx);
+                Box::new(self)
+            }),
+        )
+        .unwrap()
+    }
 
-    ui.add_space(10.0);
+    pub fn on_library_loaded(&mut self) {
+        if let Some(library) = self.sfx_library.as_ref() {
+            record_library(library);
+            if library.skipped_entries > 0 {
+                push_error(t!("library.skipped_entries", count = library.skipped_entries));
+            }
+        }
+        self.update_recently_added();
+        self.orphaned_files = None;
+    }
 
-    ui.label(format!("Total files: {}", total_files));
-    ui.label(format!(
-        "Total size: {}",
-        pretty_bytes::converter::convert(total_bytes as f64)
-    ));
-    ui.label(format!(
-        "Total duration: {}s",
-        stringify_duration(total_duration as i64)
-    ));
+    // the sound selected when the app was last closed, if it's still in the library
+    pub fn restore_selected_sfx(&mut self) {
+        let Some(id) = get_settings().last_selected_sfx else {
+            return;
+        };
+        self.selected_sfx = self.sfx_library.as_ref().and_then(|library| library.find_by_id(id).cloned());
+    }
 
-    ui.add_space(30.0);
+    pub fn update_recently_added(&mut self) {
+        self.recently_added = self
+            .sfx_library
+            .as_ref()
+            .map(|library| added_since(library, get_settings().recently_added_versions))
+            .unwrap_or_default();
+    }
 
-    ui.heading("SFX Files");
+    // only clicks and the keyboard select on purpose, hovering or restoring a selection doesn't auto play
+    fn queue_auto_play(&mut self) {
+        if get_settings().auto_play_on_select {
+            self.pending_auto_play = self.selected_sfx.as_ref().map(|sfx| (sfx.id(), Instant::now()));
+        }
+    }
 
-    ui.add_space(10.0);
+    // plays the selected sound once the selection has settled
+    fn auto_play_selection(&mut self, ctx: &egui::Context) {
+        let Some((id, selected_at)) = self.pending_auto_play else {
+            return;
+        };
+        let elapsed = selected_at.elapsed();
+        if elapsed < AUTO_PLAY_DEBOUNCE {
+            ctx.request_repaint_after(AUTO_PLAY_DEBOUNCE - elapsed);
+            return;
+        }
 
-    ui.label(format!(
-        "Downloaded sfx files: {}",
-        EXISTING_SOUND_FILES.lock().unwrap().len()
-    ));
-}
+        self.pending_auto_play = None;
+        if let Some(sfx) = self.selected_sfx.as_ref().filter(|sfx| sfx.id() == id) {
+            stop_audio();
+            play_sound(sfx, CDN_URL);
+        }
+    }
 
-fn credits_list(ui: &mut Ui, gdsfx: &mut GdSfx) {
-    ui.heading("SFX Credits");
-    ui.add_space(10.0);
-    for credits in &gdsfx.sfx_library.as_ref().unwrap().credits {
-        ui.hyperlink_to(&credits.name, &credits.link);
+    pub fn is_reloading_library(&self) -> bool {
+        self.library_reload.is_some()
     }
 
-    ui.add_space(30.0);
+    fn search_key(&self) -> SearchKey {
+        SearchKey {
+            query: fold_case(&self.search_query),
+            filters: self.filters.clone(),
+            library_version: self.sfx_version,
+            custom_sounds: self.custom_sounds.len(),
+            favourites: favourites_generation(),
+            transliterate: get_settings().transliterate_search,
+            downloaded: sound_files_generation(),
+            min_similarity: self.min_similarity(),
+            tags: tags_generation(),
+            hidden: hidden_generation(),
+            collections: collections_generation(),
+            match_categories: self.match_categories,
+        }
+    }
 
-    ui.heading("<This project>");
-    ui.hyperlink_to("GitHub", "https://github.com/SpeckyYT/gd_sfx");
-    ui.add_space(10.0);
+    // an empty query matches everything, so there's nothing to rank
+    fn min_similarity(&self) -> Option<f64> {
+        self.fuzziness
+            .filter(|_| !self.search_query.is_empty())
+            .map(|fuzziness| 1.0 - fuzziness as f64)
+    }
 
-    for (name, link) in [
-        ("Specky", "https://github.com/SpeckyYT"),
-        ("tags", "https://github.com/zTags"),
-        ("kr8gz", "https://github.com/kr8gz"),
-    ] {
-        ui.hyperlink_to(name, link);
+    fn query_matches(&self, name: &str) -> bool {
+        let query = fold_case(&self.search_query);
+        match self.min_similarity() {
+            Some(min) => name_similarity(name, &query) >= min,
+            None => name_matches(name, &query),
+        }
     }
-}
 
-fn search_bar(ui: &mut Ui, gdsfx: &mut GdSfx) {
-    ui.heading("Search");
-    ui.text_edit_singleline(&mut gdsfx.search_query);
-}
+    // filters the library on a background thread so typing stays responsive,
+    // the previous results are shown until the new ones are ready
+    fn update_search(&mut self, ctx: &egui::Context) {
+        let Some(library) = self.sfx_library.as_ref() else {
+            return;
+        };
+        let key = self.search_key();
 
-fn sort_menu(ui: &mut Ui, gdsfx: &mut GdSfx) {
-    ui.menu_button("Sorting", |ui| {
-        for (alternative, text) in [
-            (Sorting::Default, "Default"),
-            (Sorting::NameInc, "Name+"),
-            (Sorting::NameDec, "Name-"),
-            (Sorting::LengthInc, "Length+"),
-            (Sorting::LengthDec, "Length-"),
-            (Sorting::IdInc, "ID+"),
-            (Sorting::IdDec, "ID-"),
-            (Sorting::SizeInc, "Size+"),
-            (Sorting::SizeDec, "Size-"),
-        ] {
-            let response = ui.radio_value(&mut gdsfx.sorting, alternative, text);
-            if response.clicked() {
-                ui.close_menu();
-            }
+        if self.search_results.as_ref().is_some_and(|(results_key, _)| *results_key == key) {
+            self.pending_search = None;
+            return;
         }
-    });
-}
 
-fn sfx_button(ui: &mut Ui, gdsfx: &mut GdSfx, entry: &LibraryEntry) {
-    let sound = ui.button(entry.pretty_name());
-    if sound.hovered() {
-        gdsfx.selected_sfx = Some(entry.clone());
+        if self.pending_search.as_ref().is_none_or(|(pending_key, _)| *pending_key != key) {
+            // replacing the receiver discards the outdated search
+            let (sender, receiver) = unbounded();
+            let root = library.sound_effects.clone();
+            let names = library.names.clone();
+            let search_key = key.clone();
+            spawn(move || {
+                let mut scores = HashMap::default();
+                let results = filter_sounds(&root, &names, &search_key, &mut scores);
+                let _ = sender.send((results, scores));
+            });
+            self.pending_search = Some((key, receiver));
+        }
+
+        if let Some((key, receiver)) = self.pending_search.take() {
+            match receiver.try_recv() {
+                Ok((results, scores)) => {
+                    let shown = results.first().map(|root| root.sounds()).unwrap_or_default();
+                    let existing = EXISTING_SOUND_FILES.lock().unwrap();
+                    let downloaded = shown.iter().filter(|sound| existing.contains(&sound.id())).count();
+                    self.result_counts = (shown.len(), downloaded);
+                    drop(existing);
+                    self.search_results = Some((key, results));
+                    self.search_scores = scores;
+                }
+                Err(_) => {
+                    self.pending_search = Some((key, receiver));
+                    ctx.request_repaint();
+                }
+            }
+        }
     }
-    if sound.clicked() {
-        stop_audio();
-        play_sound(entry, CDN_URL);
+
+    pub fn fetch_annotations(&mut self) {
+        let (sender, receiver) = unbounded();
+        spawn(move || {
+            let diff = load_annotations().map(|annotations| annotation_diff(&annotations));
+            let _ = sender.send(diff);
+        });
+        self.annotations_fetch = Some(receiver);
     }
-    sound.context_menu(|ui| {
-        if has_favourite(entry.id()) {
-            if ui.button("Remove favourite").clicked() {
-                remove_favourite(entry.id());
-                ui.close_menu();
+
+    fn poll_annotations_fetch(&mut self, ctx: &egui::Context) {
+        let Some(receiver) = self.annotations_fetch.as_ref() else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(Ok(diff)) if diff.is_empty() => push_notification(t!("annotations.nothing_new")),
+            Ok(Ok(diff)) => self.annotations_preview = Some(diff),
+            Ok(Err(error)) => push_error(t!("annotations.error", error = error)),
+            Err(_) => {
+                ctx.request_repaint();
+                return;
             }
-        } else if ui.button("Favourite").clicked() {
-            add_favourite(entry.id());
-            ui.close_menu();
         }
-        if entry.exists() {
-            if ui.button("Delete").clicked() {
-                entry.delete();
-                ui.close_menu();
-            }
-        } else if ui.button("Download").clicked() {
-            entry.download_and_store();
-            ui.close_menu();
+        self.annotations_fetch = None;
+    }
+
+    // tags every sound at once and remembers which ones were new for undo
+    fn tag_sounds(&mut self, ctx: &egui::Context, ids: &[i64], tag: String) {
+        let added = add_tags(ids, &tag);
+        if added.is_empty() {
+            return;
         }
-    });
-}
+        let shortcut = KeyBindings::format(ctx, &self.key_bindings.undo_tags);
+        push_notification(t!("tags.tagged_many", count = added.len(), tag = tag, shortcut = shortcut));
+        self.tag_undo = Some((added, tag));
+    }
 
-fn side_bar_sfx(ctx: &egui::Context, sfx: Option<&LibraryEntry>) {
-    if let Some(sfx) = sfx {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            // ui.input(|input| {
-            // if input.modifiers.alt
-            // });
-            ui.heading(sfx.name());
+    fn undo_tags(&mut self) {
+        if let Some((ids, tag)) = self.tag_undo.take() {
+            remove_tags(&ids, &tag);
+            push_notification(t!("tags.undone", count = ids.len(), tag = tag));
+        }
+    }
 
-            ui.add_space(25.0);
+    // drops are handled by the widgets under the pointer during the frame it's released
+    fn drag_preview(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.pointer.any_released()) {
+            self.dragged_sound = None;
+            self.dragged_tag = None;
+            return;
+        }
+        let text = match (&self.dragged_sound, &self.dragged_tag) {
+            (Some(sound), _) => sound.name().to_string(),
+            (None, Some(tag)) => format!("🏷 {tag}"),
+            (None, None) => return,
+        };
+        egui::show_tooltip_at_pointer(ctx, egui::Id::new("drag_preview"), |ui| {
+            ui.label(text);
+        });
+    }
 
-            ui.code(sfx.get_string());
+    pub fn is_searching(&self) -> bool {
+        self.pending_search.is_some()
+    }
 
-            ui.add_space(25.0);
+    // drops the decoded sounds after a while without input, for running next to the game.
+    // the results stay, the list is still on screen
+    fn trim_when_idle(&mut self, ctx: &egui::Context) {
+        let interacted = ctx.input(|i| !i.events.is_empty() || i.pointer.is_moving());
+        if interacted || self.last_interaction.is_none() {
+            self.last_interaction = Some(Instant::now());
+            if self.caches_trimmed {
+                self.caches_trimmed = false;
+                ctx.request_repaint();
+            }
+        }
 
-            ui.heading(format!("ID: {}", sfx.id()));
-            ui.heading(format!("Category ID: {}", sfx.parent()));
-            ui.heading(format!("Size: {}", convert(sfx.bytes() as f64)));
-            ui.heading(format!("Duration: {}s", stringify_duration(sfx.duration())));
+        let idle_mins = get_settings().idle_unload_mins;
+        let Some(last_interaction) = self.last_interaction.filter(|_| idle_mins > 0 && !self.caches_trimmed) else {
+            return;
+        };
+        let idle = Duration::from_secs(idle_mins * 60);
+        let elapsed = last_interaction.elapsed();
+        if elapsed < idle {
+            ctx.request_repaint_after(idle - elapsed);
+            return;
+        }
 
-            ui.add_space(50.0);
+        clear_sound_cache();
+        clear_audio_info();
+        self.caches_trimmed = true;
+    }
 
-            if ui
-                .add_enabled(!sfx.exists(), Button::new("Download"))
-                .clicked()
-            {
-                sfx.download_and_store();
-            }
-            if ui
-                .add_enabled(sfx.exists(), Button::new("Delete"))
-                .clicked()
-            {
-                sfx.delete();
-            }
-            if ui.button("Play").clicked() {
-                play_sound(sfx, CDN_URL);
-            }
-            if ui.button("Stop").clicked() {
-                stop_audio();
-            }
-        });
+    // keeps the title and the now playing indicator in sync with the audio threads,
+    // "▶ GDSFX" while something is playing
+    fn poll_playback(&mut self, ctx: &egui::Context) {
+        let playing = is_playing();
+        if playing != self.title_playing {
+            let title = if playing { format!("▶ {WINDOW_TITLE}") } else { WINDOW_TITLE.to_string() };
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title));
+            self.title_playing = playing;
+        }
+        // sounds finish on their own thread, so keep checking until they do
+        if playing {
+            ctx.request_repaint_after(PLAYING_CHECK_INTERVAL);
+        }
     }
-}
 
-// chatgpt (tm)
-fn remove_empty_category_nodes(node: &mut LibraryEntry) {
-    match node {
-        LibraryEntry::Sound { .. } => {}
-        LibraryEntry::Category {
-            children, parent, ..
-        } => {
-            // Recursively remove empty Category nodes from children
-            children.retain(|child| {
-                if let LibraryEntry::Category { children, .. } = child {
-                    !children.is_empty()
-                        || children
-                            .iter()
-                            .any(|c| matches!(c, LibraryEntry::Sound { .. }))
-                        || *parent == 1
-                } else {
-                    true
-                }
-            });
+    fn poll_analyses(&mut self, ctx: &egui::Context) {
+        if !finished_analyses().is_empty() {
+            ctx.request_repaint();
+        }
+        // same as playback, the workers can't wake the gui themselves
+        if analyses_in_progress() {
+            ctx.request_repaint_after(PLAYING_CHECK_INTERVAL);
+        }
+    }
 
-            // Recursively apply to children
-            for child in children {
-                remove_empty_category_nodes(child);
-            }
+    pub fn toggle_fullscreen(&mut self, ctx: &egui::Context) {
+        self.fullscreen = !self.fullscreen;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(self.fullscreen));
+
+        let scale = if self.fullscreen { FULLSCREEN_FONT_SCALE } else { 1.0 / FULLSCREEN_FONT_SCALE };
+        let mut style = (*ctx.style()).clone();
+        for font in style.text_styles.values_mut() {
+            font.size *= scale;
         }
+        ctx.set_style(style);
     }
-}
 
-fn filter_sounds(tree: &LibraryEntry, filter_str: &str) -> Vec<LibraryEntry> {
-    match tree {
-        LibraryEntry::Sound { name, .. } => {
-            if name.to_ascii_lowercase().contains(filter_str) {
-                vec![tree.clone()] // Keep the sound if it contains the filter string
-            } else {
-                vec![] // Filter out the sound if it doesn't contain the filter string
-            }
+    pub fn retry_from_safe_mode(&mut self, clear_cache: bool) {
+        if clear_cache {
+            let _ = fs::remove_file(SFX_LIBRARY_FILE.as_path());
         }
-        LibraryEntry::Category {
-            id,
-            name,
-            parent,
-            children,
-        } => {
+        leave_safe_mode();
+        self.sorting = get_settings().default_sorting;
+        check_for_updates();
+        self.reload_library();
+    }
+
+    pub fn reload_library(&mut self) {
+        if self.is_reloading_library() {
+            return;
+        }
+
+        // also picks up sound files added or removed outside the app
+        check_all_sfx_files();
+        self.cache_history = None;
+
+        self.load_library(true);
+    }
+
+    // the requests run on their own thread, poll_library_reload picks up the result
+    fn load_library(&mut self, force_version: bool) {
+        let (sender, receiver) = unbounded();
+        let mut loader = GdSfx {
+            cdn_url: self.cdn_url.clone(),
+            ..Default::default()
+        };
+        spawn(move || {
+            loader.get_sfx_version(force_version);
+            loader.get_sfx_library(false);
+            let _ = sender.send((loader.cdn_url, loader.sfx_version, loader.sfx_library));
+        });
+        self.library_reload = Some(receiver);
+    }
+
+    fn poll_library_reload(&mut self, ctx: &egui::Context) {
+        let Some(receiver) = &self.library_reload else {
+            return;
+        };
+
+        match receiver.try_recv() {
+            Ok((cdn_url, version, Some(mut library))) => {
+                let first_load = self.sfx_library.is_none();
+                for sound in &self.custom_sounds {
+                    library.add_custom_sound(sound.clone());
+                }
+                self.selected_sfx = self
+                    .selected_sfx
+                    .as_ref()
+                    .and_then(|sfx| library.find_by_id(sfx.id()).cloned());
+                self.cdn_url = cdn_url.or(self.cdn_url.take());
+                self.sfx_version = version.or(self.sfx_version);
+                self.sfx_library = Some(library);
+                self.library_reload = None;
+                self.on_library_loaded();
+                if first_load {
+                    self.restore_selected_sfx();
+                }
+                self.new_library_version = None;
+                self.safe_mode = false;
+                ctx.request_repaint();
+            }
+            Ok((_, _, None)) | Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                push_error(t!("error.reload_library"));
+                self.library_reload = None;
+            }
+            Err(crossbeam_channel::TryRecvError::Empty) => ctx.request_repaint(),
+        }
+    }
+
+    fn import_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
+        for file in dropped_files {
+            let data = if let Some(bytes) = &file.bytes {
+                bytes.to_vec()
+            } else if let Some(path) = &file.path {
+                match std::fs::read(path) {
+                    Ok(data) => data,
+                    Err(error) => {
+                        push_error(t!("error.read_file", path = path.display(), error = error));
+                        continue;
+                    }
+                }
+            } else {
+                continue;
+            };
+
+            let name = file
+                .path
+                .as_ref()
+                .and_then(|path| path.file_stem())
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or(file.name);
+
+            if !is_ogg(&data) {
+                push_error(t!("error.not_ogg", name = name));
+                continue;
+            }
+            let Some(duration) = ogg_duration(&data) else {
+                push_error(t!("error.decode", name = name));
+                continue;
+            };
+
+            let Some(library) = self.sfx_library.as_mut() else {
+                continue;
+            };
+            let sound: LibraryEntry = SoundEntry {
+                id: CUSTOM_CATEGORY_ID - 1 - self.custom_sounds.len() as i64,
+                name,
+                parent: CUSTOM_CATEGORY_ID,
+                bytes: data.len() as i64,
+                duration,
+                original_index: NO_ORIGINAL_INDEX,
+            }
+            .into();
+            LOCAL_SFX_LIBRARY.lock().insert(sound.id(), data);
+            library.add_custom_sound(sound.clone());
+            self.custom_sounds.push(sound);
+        }
+    }
+
+    fn poll_version_check(&mut self, ctx: &egui::Context) {
+        if let Some(receiver) = &self.version_check {
+            match receiver.try_recv() {
+                Ok(version) => {
+                    // a cdn still serving an older version isn't an update
+                    if version.is_some() && version > self.sfx_version {
+                        self.new_library_version = version;
+                    }
+                    self.version_check = None;
+                }
+                Err(crossbeam_channel::TryRecvError::Empty) => {}
+                Err(crossbeam_channel::TryRecvError::Disconnected) => self.version_check = None,
+            }
+        }
+
+        let interval_mins = get_settings().auto_version_check_interval_mins;
+        if interval_mins == 0 || is_offline() {
+            return;
+        }
+        let interval = Duration::from_secs(interval_mins * 60);
+
+        // the version was already fetched on startup
+        let last_check = *self.last_version_check.get_or_insert_with(Instant::now);
+        let elapsed = last_check.elapsed();
+
+        if elapsed < interval {
+            ctx.request_repaint_after(interval - elapsed);
+            return;
+        }
+
+        self.last_version_check = Some(Instant::now());
+        if self.version_check.is_none() && !self.is_reloading_library() {
+            let (sender, receiver) = unbounded();
+            let mut loader = GdSfx {
+                cdn_url: self.cdn_url.clone(),
+                ..Default::default()
+            };
+            spawn(move || {
+                let _ = sender.send(loader.get_sfx_version(true));
+            });
+            self.version_check = Some(receiver);
+        }
+    }
+}
+
+fn top_panel(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+        ui.add_space(4.0);
+        egui::ScrollArea::horizontal()
+            .id_source("stage_tabs")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    Stage::iter().for_each(|stage| {
+                        let response =
+                            ui.selectable_value(&mut gdsfx.stage, stage, stage.to_string());
+                        // keep the selected tab reachable on narrow windows
+                        if stage == gdsfx.stage && gdsfx.scrolled_to_stage != Some(stage) {
+                            response.scroll_to_me(None);
+                            gdsfx.scrolled_to_stage = Some(stage);
+                        }
+                    });
+                    ui.separator();
+                    if gdsfx.is_reloading_library() {
+                        ui.spinner();
+                    } else if ui
+                        .button(t!("top.reload_library"))
+                        .on_hover_text(KeyBindings::format(ctx, &gdsfx.key_bindings.reload_library))
+                        .clicked()
+                    {
+                        gdsfx.reload_library();
+                    }
+                    if ui
+                        .button("?")
+                        .on_hover_text(t!("top.shortcuts_hint", keys = KeyBindings::format(ctx, &gdsfx.key_bindings.show_help)))
+                        .clicked()
+                    {
+                        gdsfx.show_help = true;
+                    }
+                    now_playing_indicator(ui, gdsfx);
+                });
+            });
+        if let Some(version) = gdsfx.new_library_version {
+            ui.horizontal(|ui| {
+                ui.label(t!("top.new_library_version", version = version));
+                if ui.button(t!("top.reload")).clicked() {
+                    gdsfx.reload_library();
+                }
+            });
+        }
+        if using_cached_library() {
+            let version = gdsfx
+                .sfx_library
+                .as_ref()
+                .and_then(library_version)
+                .map(|version| version.to_string())
+                .unwrap_or_else(|| "v?".to_string());
+            ui.horizontal(|ui| {
+                ui.colored_label(ui.visuals().warn_fg_color, t!("top.cached_library", version = version))
+                    .on_hover_text(t!("top.cached_library_hint"));
+                if ui.add_enabled(!gdsfx.is_reloading_library(), Button::new(t!("top.retry"))).clicked() {
+                    gdsfx.reload_library();
+                }
+            });
+        }
+        if let Some((version, url)) = AVAILABLE_UPDATE.lock().unwrap().as_ref() {
+            ui.horizontal(|ui| {
+                ui.label(t!("top.update_available", version = version));
+                ui.hyperlink_to(t!("top.view_release"), url);
+            });
+        }
+        if gdsfx.recovery_state.is_some() {
+            ui.horizontal(|ui| {
+                ui.label(t!("top.crash_recovery"));
+                if ui.button(t!("top.crash_restore")).clicked() {
+                    if let Some(state) = gdsfx.recovery_state.take() {
+                        state.restore(gdsfx);
+                    }
+                }
+                if ui.button(t!("top.crash_dismiss")).clicked() {
+                    gdsfx.recovery_state = None;
+                }
+            });
+        }
+        ui.add_space(2.0);
+    });
+}
+
+fn now_playing_indicator(ui: &mut Ui, gdsfx: &GdSfx) {
+    let Some(id) = now_playing() else {
+        return;
+    };
+    let Some(sound) = gdsfx.sfx_library.as_ref().and_then(|library| library.find_by_id(id)) else {
+        return;
+    };
+    ui.separator();
+    ui.label(t!("top.now_playing", name = sound.name()));
+    if ui.small_button("⏹").on_hover_text(t!("sound.stop")).clicked() {
+        stop_audio();
+    }
+}
+
+fn safe_mode_panel(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.heading(t!("safe_mode.title"));
+            ui.label(t!("safe_mode.description"));
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                let reloading = gdsfx.is_reloading_library();
+                if ui.add_enabled(!reloading, Button::new(t!("safe_mode.retry"))).clicked() {
+                    gdsfx.retry_from_safe_mode(false);
+                }
+                if ui
+                    .add_enabled(!reloading, Button::new(t!("safe_mode.clear_cache_retry")))
+                    .on_hover_text(t!("safe_mode.clear_cache_hint"))
+                    .clicked()
+                {
+                    gdsfx.retry_from_safe_mode(true);
+                }
+                if reloading {
+                    ui.spinner();
+                }
+            });
+            ui.separator();
+            settings_list(ui, gdsfx);
+        });
+    });
+}
+
+fn main_scroll_area(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    // the library only shares the width with the details when they're on the right and shown
+    if get_settings().detail_panel_position != DetailPanelPosition::Right || gdsfx.selected_sfx.is_none() {
+        egui::CentralPanel::default().show(ctx, |ui| library_panel(ui, gdsfx));
+        return;
+    }
+    let mut panel = egui::SidePanel::left("left_panel");
+    if gdsfx.fullscreen {
+        // keep the windowed ratio between the library and the detail panel
+        panel = panel.exact_width(ctx.screen_rect().width() * LIBRARY_WIDTH / TOTAL_WIDTH);
+    }
+    panel.show(ctx, |ui| library_panel(ui, gdsfx));
+}
+
+fn library_panel(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    /*
+    // reconsider these
+    if let Some(version) = gdsfx.sfx_version {
+        ui.heading(format!("Library version: {version}"));
+    }
+    if ui.button("Force-update library").clicked() {
+        gdsfx.get_sfx_library(true);
+    }
+    ui.separator();
+    */
+
+    if let Stage::Credits = gdsfx.stage {
+        search_bar(ui, gdsfx);
+        ui.separator();
+    }
+    if let Stage::Library | Stage::Favourites = gdsfx.stage {
+        search_bar(ui, gdsfx);
+        if gdsfx.stage == Stage::Library
+            && gdsfx.unfocused_category_open.is_some()
+            && ui.button(t!("library.back_to_full_tree")).clicked()
+        {
+            unfocus_category(gdsfx);
+        }
+        ui.horizontal(|ui| {
+            sort_menu(ui, gdsfx);
+            filter_menu(ui, gdsfx);
+            preset_menu(ui, gdsfx);
+            if gdsfx.stage == Stage::Library {
+                let mut settings = get_settings();
+                if ui
+                    .checkbox(&mut settings.categories_only, t!("library.categories_only"))
+                    .on_hover_text(t!("library.categories_only_hint"))
+                    .changed()
+                {
+                    set_settings(settings);
+                }
+            }
+        });
+        filter_chips(ui, gdsfx);
+        bulk_actions(ui, gdsfx);
+        tag_palette(ui, gdsfx);
+        if gdsfx.stage == Stage::Library {
+            metadata_bar(ui, gdsfx);
+        }
+        ui.separator();
+    }
+    gdsfx.hovering_sound = false;
+    // only the tree records offsets, so the index disappears with it
+    let offsets = std::mem::take(&mut gdsfx.jump_offsets);
+    if gdsfx.stage == Stage::Library && !offsets.is_empty() {
+        egui::SidePanel::right("jump_index")
+            .resizable(false)
+            .exact_width(JUMP_INDEX_WIDTH)
+            .show_inside(ui, |ui| jump_index(ui, gdsfx, &offsets));
+    }
+    let mut list = egui::ScrollArea::vertical();
+    if let Some(offset) = gdsfx.jump_to.take() {
+        list = list.vertical_scroll_offset(offset);
+    }
+    let list = list.show(ui, |ui| {
+        if let Some(sfx_library) = gdsfx.sfx_library.as_ref() {
+            match gdsfx.stage {
+                Stage::Library => match gdsfx.search_results.clone() {
+                    Some((key, mut sfx)) if !sfx.is_empty() => {
+                        remove_empty_category_nodes(&mut sfx[0]);
+                        let searching = !key.query.is_empty() || !key.filters.is_empty();
+                        match get_settings().result_layout {
+                            ResultLayout::Flat if searching => flat_results(ui, gdsfx, &sfx[0]),
+                            ResultLayout::Grouped if searching => grouped_results(ui, gdsfx, &sfx[0]),
+                            _ => {
+                                insert_recently_added(&mut sfx[0], &gdsfx.recently_added);
+                                library_list(ui, gdsfx, &sfx[0]);
+                            }
+                        }
+                    }
+                    Some(_) => no_results(ui, gdsfx),
+                    None => {} // first search still running
+                },
+                Stage::Favourites => {
+                    favourites_list(ui, gdsfx, sfx_library.sound_effects.clone())
+                }
+                Stage::Collections => collections_list(ui, gdsfx),
+                Stage::Stats => stats_list(ui, gdsfx),
+                Stage::Settings => settings_list(ui, gdsfx),
+                Stage::Credits => credits_list(ui, gdsfx),
+            }
+        } else if gdsfx.is_reloading_library() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(t!("library.loading"));
+            });
+        }
+    });
+    if !gdsfx.hovering_sound {
+        // a preview that already finished leaves whatever is playing now alone
+        if let Some((id, _, true)) = gdsfx.hover_preview.take() {
+            if now_playing() == Some(id) {
+                stop_audio();
+            }
+        }
+    }
+    // a tag chip dropped onto the list filters by that tag
+    if let Some(tag) = gdsfx.dragged_tag.clone() {
+        if ui.input(|i| i.pointer.any_released()) && ui.rect_contains_pointer(list.inner_rect) {
+            gdsfx.filters.tag = Some(tag);
+            if gdsfx.stage != Stage::Favourites {
+                gdsfx.stage = Stage::Library;
+            }
+        }
+    }
+}
+
+// counts are taken when the results arrive and the library loads, the bar only reads them
+fn metadata_bar(ui: &mut Ui, gdsfx: &GdSfx) {
+    let items = settings::SETTINGS.lock().unwrap().metadata_bar.clone();
+    let Some(library) = gdsfx.sfx_library.as_ref() else {
+        return;
+    };
+    if items.is_empty() {
+        return;
+    }
+    let (shown, downloaded) = gdsfx.result_counts;
+
+    ui.horizontal_wrapped(|ui| {
+        let mut first = true;
+        for item in MetadataItem::iter().filter(|item| items.contains(item)) {
+            let text = match item {
+                MetadataItem::Counts => t!(
+                    "metadata.counts",
+                    shown = shown,
+                    total = library.sound_count
+                ),
+                MetadataItem::Downloaded => t!("metadata.downloaded", count = downloaded),
+                MetadataItem::Version => {
+                    let version = gdsfx
+                        .sfx_version
+                        .or_else(|| library_version(library))
+                        .map(|version| version.to_string())
+                        .unwrap_or_else(|| "v?".to_string());
+                    t!("metadata.version", version = version)
+                }
+                MetadataItem::Cdn => match gdsfx.cdn_url.as_deref() {
+                    Some(url) => t!("metadata.cdn", url = url),
+                    None => t!("metadata.no_cdn").to_string(),
+                },
+            };
+            if !first {
+                ui.weak("·");
+            }
+            first = false;
+            ui.small(text);
+        }
+    });
+}
+
+fn tag_palette(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    let palette = palette_tags();
+    if palette.is_empty() {
+        return;
+    }
+    egui::CollapsingHeader::new(t!("tags.palette"))
+        .id_source("tag_palette")
+        .show(ui, |ui| {
+            ui.weak(t!("tags.palette_hint"));
+            let released = ui.input(|i| i.pointer.any_released());
+            ui.horizontal_wrapped(|ui| {
+                for tag in palette {
+                    let [r, g, b, _] = tag.color.to_array();
+                    let is_light = r as u32 * 299 + g as u32 * 587 + b as u32 * 114 > 128_000;
+                    let text_color = if is_light { egui::Color32::BLACK } else { egui::Color32::WHITE };
+                    let chip = ui.add(
+                        Button::new(egui::RichText::new(&tag.name).color(text_color))
+                            .fill(tag.color)
+                            .small()
+                            .rounding(8.0)
+                            .sense(egui::Sense::drag()),
+                    );
+                    if chip.drag_started() {
+                        gdsfx.dragged_tag = Some(tag.name.clone());
+                    }
+                    if released && ui.rect_contains_pointer(chip.rect) {
+                        if let Some(sound) = gdsfx.dragged_sound.as_ref() {
+                            add_tag(sound.id(), &tag.name);
+                            push_notification(t!("tags.tagged", name = sound.name(), tag = tag.name));
+                        }
+                    }
+                }
+            });
+        });
+}
+
+// a–z down the side of the list, jumping to the first row starting with the letter
+fn jump_index(ui: &mut Ui, gdsfx: &mut GdSfx, offsets: &HashMap<char, f32>) {
+    ui.spacing_mut().item_spacing.y = 0.0;
+    for letter in ('A'..='Z').chain(std::iter::once(JUMP_INDEX_OTHER)) {
+        let offset = offsets.get(&letter).copied();
+        let button = Button::new(egui::RichText::new(letter.to_string()).small()).frame(false);
+        if ui.add_enabled(offset.is_some(), button).clicked() {
+            gdsfx.jump_to = offset;
+        }
+    }
+}
+
+// names starting with anything but a latin letter share one entry
+fn jump_letter(name: &str) -> char {
+    match name.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => c.to_ascii_uppercase(),
+        _ => JUMP_INDEX_OTHER,
+    }
+}
+
+fn library_list(ui: &mut Ui, gdsfx: &mut GdSfx, sfx_library: &LibraryEntry) {
+    fn recursive(gdsfx: &mut GdSfx, entry: &LibraryEntry, ui: &mut egui::Ui, top: f32) {
+        if entry.parent() != 0 && !gdsfx.in_virtual_category && entry.id() != RECENTLY_ADDED_CATEGORY_ID {
+            let offset = ui.cursor().top() - top;
+            gdsfx.jump_offsets.entry(jump_letter(entry.name())).or_insert(offset);
+        }
+        match <&CategoryEntry>::try_from(entry) {
+            Ok(category) => {
+                let (mut sounds, mut categories): (Vec<_>, Vec<_>) =
+                    category.children.iter().partition(|x| !x.is_category());
+
+                sort_entries(&mut categories, gdsfx.sorting, &gdsfx.search_scores);
+                sort_entries(&mut sounds, gdsfx.sorting, &gdsfx.search_scores);
+                // the virtual category always stays at the top
+                categories.sort_by_key(|c| c.id() != RECENTLY_ADDED_CATEGORY_ID);
+
+                if category.parent == 0 {
+                    // root
+                    let pinned = get_settings().pinned_categories;
+                    let pinned = pinned.iter().filter_map(|id| entry.find(*id)).collect::<Vec<_>>();
+                    for category in &pinned {
+                        ui.push_id(("pinned", category.id()), |ui| {
+                            recursive(gdsfx, category, ui, top);
+                        });
+                    }
+                    if !pinned.is_empty() {
+                        ui.separator();
+                    }
+                    for child in categories {
+                        gdsfx.in_virtual_category = child.id() == RECENTLY_ADDED_CATEGORY_ID;
+                        recursive(gdsfx, child, ui, top);
+                    }
+                    gdsfx.in_virtual_category = false;
+                } else {
+                    let is_disabled = sounds.is_empty() && categories.is_empty(); // an empty query will always match everything
+
+                    let name = if is_pinned(category.id) {
+                        format!("📌 {}", category.name)
+                    } else {
+                        category.name.to_string()
+                    };
+                    let name = match category_progress(category.id) {
+                        Some(progress) => {
+                            ui.ctx().request_repaint();
+                            if progress.deleting {
+                                t!("category.deleting", name = name, done = progress.done, total = progress.total)
+                            } else {
+                                t!("category.downloading", name = name, done = progress.done, total = progress.total)
+                            }
+                        }
+                        None => name,
+                    };
+
+                    // the library history records category ids as well
+                    let is_new = gdsfx.recently_added.contains(&category.id);
+
+                    ui.add_enabled_ui(!is_disabled, |ui| {
+                        let open = gdsfx.category_open.get(&category.id).copied().unwrap_or(false);
+                        let id = ui.make_persistent_id(("category", category.id));
+                        let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, false);
+                        state.set_open(open);
+
+                        let (toggle, header, _) = state
+                            .show_header(ui, |ui| {
+                                let label = ui.add(egui::Label::new(name).sense(egui::Sense::click()));
+                                if is_new {
+                                    ui.label(
+                                        egui::RichText::new(t!("category.new"))
+                                            .small()
+                                            .color(ui.visuals().warn_fg_color),
+                                    )
+                                    .on_hover_text(t!("category.new_hint"));
+                                }
+                                category_note_button(ui, category.id);
+                                label
+                            })
+                            .body(|ui| {
+                                for child in categories {
+                                    recursive(gdsfx, child, ui, top);
+                                }
+                                // search matches are always shown
+                                let collapsible = !sounds.is_empty()
+                                    && gdsfx.search_query.is_empty()
+                                    && categories_only();
+                                let revealed = gdsfx.revealed_categories.contains(&category.id);
+                                if collapsible && !revealed {
+                                    if ui.small_button(t!("library.show_sounds", count = sounds.len())).clicked() {
+                                        gdsfx.revealed_categories.insert(category.id);
+                                    }
+                                    return;
+                                }
+                                if collapsible && ui.small_button(t!("library.hide_sounds")).clicked() {
+                                    gdsfx.revealed_categories.remove(&category.id);
+                                }
+                                for child in sounds {
+                                    recursive(gdsfx, child, ui, top);
+                                }
+                            });
+
+                        let mut label = header.inner;
+                        if toggle.clicked() || label.clicked() {
+                            gdsfx.category_open.insert(category.id, !open);
+                        }
+                        if let Some(note) = get_category_note(category.id) {
+                            label = label.on_hover_text(note);
+                        }
+                        category_context_menu(label, gdsfx, entry);
+                    });
+                }
+            }
+            Err(_) => sfx_button(ui, gdsfx, entry),
+        }
+    }
+    // the list is the first thing in the scroll area, so this is where its content starts
+    let top = ui.min_rect().top();
+    recursive(gdsfx, sfx_library, ui, top);
+}
+
+fn sort_entries(entries: &mut [&LibraryEntry], sorting: Sorting, scores: &SearchScores) {
+    entries.sort_by(|a, b| match sorting {
+        // the filtered tree is rebuilt, so its order can't be relied on
+        Sorting::Default => a.original_index().cmp(&b.original_index()),
+        Sorting::NameInc => a.name().cmp(b.name()),
+        Sorting::NameDec => b.name().cmp(a.name()),
+        Sorting::LengthInc => a.duration().cmp(&b.duration()),
+        Sorting::LengthDec => b.duration().cmp(&a.duration()),
+        Sorting::IdInc => b.id().cmp(&a.id()), // this is not a bug, in gd, the id sorting is reversed,
+        Sorting::IdDec => a.id().cmp(&b.id()), // in-game it's `ID+ => 9 - 0; ID- => 0 - 9`
+        Sorting::SizeInc => a.bytes().cmp(&b.bytes()),
+        Sorting::SizeDec => b.bytes().cmp(&a.bytes()),
+    });
+    if !scores.is_empty() {
+        // fuzzy results go by similarity first, the chosen sorting breaks ties
+        let score = |entry: &&LibraryEntry| scores.get(&entry.id()).copied().unwrap_or(0.0);
+        entries.sort_by(|a, b| score(b).total_cmp(&score(a)));
+    }
+}
+
+fn flat_results(ui: &mut Ui, gdsfx: &mut GdSfx, results: &LibraryEntry) {
+    let mut sounds = results.sounds();
+    sort_entries(&mut sounds, gdsfx.sorting, &gdsfx.search_scores);
+    for sound in sounds {
+        sfx_button(ui, gdsfx, sound);
+    }
+}
+
+// every category with matching sounds directly in it, with its match count
+fn grouped_results(ui: &mut Ui, gdsfx: &mut GdSfx, results: &LibraryEntry) {
+    fn collect<'a>(entry: &'a LibraryEntry, groups: &mut Vec<&'a LibraryEntry>) {
+        if let Some(children) = entry.children() {
+            if children.iter().any(LibraryEntry::is_sound) {
+                groups.push(entry);
+            }
+            for child in children {
+                collect(child, groups);
+            }
+        }
+    }
+    let mut groups = Vec::new();
+    collect(results, &mut groups);
+    sort_entries(&mut groups, gdsfx.sorting, &gdsfx.search_scores);
+
+    for group in groups {
+        let mut sounds = group.children().into_iter().flatten().filter(|x| x.is_sound()).collect::<Vec<_>>();
+        sort_entries(&mut sounds, gdsfx.sorting, &gdsfx.search_scores);
+        egui::CollapsingHeader::new(t!("library.group", name = group.name(), count = sounds.len()))
+            .id_source(("result_group", group.id()))
+            .default_open(true)
+            .show(ui, |ui| {
+                for sound in sounds {
+                    sfx_button(ui, gdsfx, sound);
+                }
+            });
+    }
+}
+
+fn category_context_menu(response: egui::Response, gdsfx: &mut GdSfx, entry: &LibraryEntry) {
+    response.context_menu(|ui| {
+        if ui.button(t!("category.focus")).clicked() {
+            focus_category(gdsfx, entry.id());
+            ui.close_menu();
+        }
+        if is_pinned(entry.id()) {
+            if ui.button(t!("category.unpin")).clicked() {
+                unpin_category(entry.id());
+                ui.close_menu();
+            }
+        } else if ui.button(t!("category.pin")).clicked() {
+            pin_category(entry.id());
+            ui.close_menu();
+        }
+        hide_button(ui, entry);
+        ui.separator();
+        // the list only holds the filtered tree, so act on the full category
+        let library = gdsfx.sfx_library.as_ref();
+        let category = library.and_then(|library| library.find_by_id(entry.id())).unwrap_or(entry);
+        let running = category_progress(entry.id()).is_some();
+        if ui
+            .add_enabled(!running && !is_offline(), Button::new(t!("category.download_all")))
+            .clicked()
+        {
+            gdsfx.confirm_category_download = Some((category.id(), false));
+            ui.close_menu();
+        }
+        if ui
+            .add_enabled(!running, Button::new(t!("category.delete_all")))
+            .clicked()
+        {
+            delete_category(category);
+            ui.close_menu();
+        }
+    });
+}
+
+fn hide_button(ui: &mut Ui, entry: &LibraryEntry) {
+    if is_hidden(entry.id()) {
+        if ui.button(t!("hidden.unhide")).clicked() {
+            unhide(entry.id());
+            ui.close_menu();
+        }
+    } else if ui.button(t!("hidden.hide")).on_hover_text(t!("hidden.hide_hint")).clicked() {
+        hide(entry.id());
+        ui.close_menu();
+    }
+}
+
+fn category_note_button(ui: &mut Ui, id: i64) {
+    ui.menu_button("ℹ", |ui| {
+        ui.label(t!("category.note"));
+        let mut note = get_category_note(id).unwrap_or_default();
+        if ui.text_edit_multiline(&mut note).changed() {
+            set_category_note(id, &note);
+        }
+    })
+    .response
+    .on_hover_text(t!("category.edit_note"));
+}
+
+fn focus_category(gdsfx: &mut GdSfx, id: i64) {
+    let Some(library) = gdsfx.sfx_library.as_ref() else {
+        return;
+    };
+    if gdsfx.unfocused_category_open.is_none() {
+        gdsfx.unfocused_category_open = Some(gdsfx.category_open.clone());
+    }
+    gdsfx.category_open = library
+        .path_to(id)
+        .into_iter()
+        .map(|entry| (entry.id(), true))
+        .collect();
+}
+
+fn unfocus_category(gdsfx: &mut GdSfx) {
+    if let Some(category_open) = gdsfx.unfocused_category_open.take() {
+        gdsfx.category_open = category_open;
+    }
+}
+
+fn favourites_list(ui: &mut Ui, gdsfx: &mut GdSfx, sfx_library: LibraryEntry) {
+    // returns the amount of shown sounds
+    fn recursive(gdsfx: &mut GdSfx, entry: &LibraryEntry, ui: &mut egui::Ui) -> usize {
+        match <&CategoryEntry>::try_from(entry) {
+            Ok(category) => category
+                .children
+                .iter()
+                .map(|child| recursive(gdsfx, child, ui))
+                .sum(),
+            Err(sound) => {
+                if has_favourite(sound.id)
+                    && gdsfx.filters.matches(entry)
+                    && gdsfx.query_matches(&sound.name)
+                {
+                    sfx_button(ui, gdsfx, entry);
+                    1
+                } else {
+                    0
+                }
+            }
+        }
+    }
+    if recursive(gdsfx, &sfx_library, ui) == 0 {
+        if favourites::FAVOURITES_LIST.lock().unwrap().is_empty() {
+            ui.label(t!("favourites.empty"));
+        } else {
+            no_results(ui, gdsfx);
+        }
+    }
+}
+
+fn no_results(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    if gdsfx.search_query.is_empty() {
+        ui.label(t!("search.no_matches"));
+    } else {
+        ui.label(t!("search.no_results", query = gdsfx.search_query));
+
+        let query = fold_case(&gdsfx.search_query);
+        if gdsfx.search_suggestions.as_ref().is_none_or(|(q, _)| *q != query) {
+            let suggestions = gdsfx
+                .sfx_library
+                .as_ref()
+                .map(|library| did_you_mean(&library.sound_effects, &query))
+                .unwrap_or_default();
+            gdsfx.search_suggestions = Some((query, suggestions));
+        }
+        let suggestions = gdsfx.search_suggestions.as_ref().map(|(_, s)| s.clone()).unwrap_or_default();
+        if !suggestions.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(t!("search.did_you_mean"));
+                for suggestion in suggestions {
+                    if ui.link(format!("{suggestion}?")).clicked() {
+                        gdsfx.search_query = suggestion;
+                    }
+                }
+            });
+        }
+    }
+    ui.horizontal(|ui| {
+        if !gdsfx.search_query.is_empty() && ui.button(t!("search.clear_search")).clicked() {
+            gdsfx.search_query.clear();
+        }
+        if !gdsfx.filters.is_empty() && ui.button(t!("search.clear_filters")).clicked() {
+            gdsfx.filters = Filters::default();
+        }
+    });
+}
+
+fn collections_list(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    ui.heading(t!("stage.collections"));
+    ui.add_space(10.0);
+
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut gdsfx.new_collection_name);
+        let name = gdsfx.new_collection_name.trim().to_string();
+        if ui.add_enabled(!name.is_empty(), Button::new(t!("collections.new"))).clicked() {
+            add_collection(&name);
+            gdsfx.new_collection_name.clear();
+        }
+    });
+
+    ui.add_space(10.0);
+
+    let collections = get_collections();
+    if collections.is_empty() {
+        ui.label(t!("collections.empty"));
+    }
+
+    // applied after the loop so the indices stay valid while drawing
+    let mut removed_sound = None;
+    let mut removed_collection = None;
+    for (index, collection) in collections.into_iter().enumerate() {
+        egui::CollapsingHeader::new(&collection.name)
+            .id_source(("collection", index))
+            .show(ui, |ui| {
+                let mut description = collection.description.clone();
+                if ui
+                    .add(egui::TextEdit::multiline(&mut description).hint_text(t!("collections.description")).desired_rows(2))
+                    .changed()
+                {
+                    set_collection_description(index, &description);
+                }
+                for id in &collection.entries {
+                    let entry = gdsfx
+                        .sfx_library
+                        .as_ref()
+                        .and_then(|library| library.find_by_id(*id))
+                        .cloned();
+                    ui.horizontal(|ui| {
+                        if ui.small_button("❌").on_hover_text(t!("collections.remove_sound")).clicked() {
+                            removed_sound = Some((index, *id));
+                        }
+                        match &entry {
+                            Some(entry) => sfx_button(ui, gdsfx, entry),
+                            None => {
+                                ui.weak(t!("collections.missing_sound", id = id));
+                            }
+                        }
+                    });
+                }
+                ui.horizontal(|ui| {
+                    if ui.button(t!("collections.export")).clicked() {
+                        let file_name = collection
+                            .name
+                            .chars()
+                            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                            .collect::<String>();
+                        let path = GD_FOLDER.join(format!("gdsfx_{file_name}_triggers.txt"));
+                        if let Some(library) = gdsfx.sfx_library.as_ref() {
+                            match export_collection_trigger_script(&collection, library, &path) {
+                                Ok(()) => push_notification(t!("collections.exported", path = path.display())),
+                                Err(error) => push_error(t!("collections.export_failed", error = error)),
+                            }
+                        }
+                    }
+                    if ui.button(t!("collections.delete")).clicked() {
+                        removed_collection = Some(index);
+                    }
+                });
+            });
+    }
+    if let Some((index, id)) = removed_sound {
+        remove_from_collection(index, id);
+    }
+    if let Some(index) = removed_collection {
+        remove_collection(index);
+    }
+}
+
+fn failed_downloads(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    let failed = get_failed_downloads();
+    if failed.is_empty() {
+        return;
+    }
+    let Some(library) = gdsfx.sfx_library.as_ref() else {
+        return;
+    };
+    let sounds = failed
+        .iter()
+        .filter_map(|id| library.find_by_id(*id))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    ui.label(t!("failed_downloads.title", count = failed.len()));
+    egui::ScrollArea::vertical()
+        .id_source("failed_downloads")
+        .max_height(150.0)
+        .show(ui, |ui| {
+            for sound in &sounds {
+                sfx_button(ui, gdsfx, sound);
+            }
+        });
+    ui.horizontal(|ui| {
+        let progress = retry_progress();
+        if ui
+            .add_enabled(progress.is_none() && !is_offline(), Button::new(t!("failed_downloads.retry_all")))
+            .clicked()
+        {
+            retry_failed_downloads(sounds);
+        }
+        if ui.add_enabled(progress.is_none(), Button::new(t!("failed_downloads.clear"))).clicked() {
+            clear_failed_downloads();
+        }
+        if let Some((done, total)) = progress {
+            ui.spinner();
+            ui.label(t!("failed_downloads.retrying", done = done, total = total));
+            ui.ctx().request_repaint();
+        }
+    });
+}
+
+fn orphaned_files_warning(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    let Some(library) = gdsfx.sfx_library.as_ref() else {
+        return;
+    };
+    // looked for again only after the files changed, a library load resets it as well
+    let generation = sound_files_generation();
+    if gdsfx.orphaned_files.as_ref().is_none_or(|(cached, _, _)| *cached != generation) {
+        let ids = orphaned_files(library);
+        let size = orphaned_size(&ids);
+        gdsfx.orphaned_files = Some((generation, ids, size));
+    }
+    let Some((_, ids, size)) = gdsfx.orphaned_files.as_ref() else {
+        return;
+    };
+    if ids.is_empty() {
+        return;
+    }
+
+    ui.add_space(10.0);
+    ui.horizontal_wrapped(|ui| {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            t!("orphaned.warning", count = ids.len(), size = convert(*size as f64)),
+        )
+        .on_hover_text(t!("orphaned.hint"));
+        if ui.button(t!("orphaned.clean_up")).clicked() {
+            gdsfx.confirm_orphan_cleanup = true;
+        }
+    });
+}
+
+// the files can't be brought back, unlike the library ones they're not downloaded again
+fn orphan_cleanup_dialog(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    if !gdsfx.confirm_orphan_cleanup {
+        return;
+    }
+    let Some((_, ids, size)) = gdsfx.orphaned_files.clone() else {
+        gdsfx.confirm_orphan_cleanup = false;
+        return;
+    };
+
+    let modal = Modal::new(ctx, "orphan_cleanup");
+    modal.open();
+    modal.show(|ui| {
+        modal.title(ui, t!("bulk.confirm"));
+        modal.frame(ui, |ui| {
+            modal.body(ui, t!("orphaned.confirm", count = ids.len(), size = convert(size as f64)));
+        });
+        modal.buttons(ui, |ui| {
+            if modal.button(ui, t!("common.cancel")).clicked() {
+                gdsfx.confirm_orphan_cleanup = false;
+            }
+            if modal.suggested_button(ui, t!("orphaned.clean_up")).clicked() {
+                delete_orphaned_files(ids);
+                gdsfx.confirm_orphan_cleanup = false;
+            }
+        });
+    });
+}
+
+fn outdated_files(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    let progress = redownload_progress();
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(progress.is_none() && gdsfx.sfx_library.is_some(), Button::new(t!("outdated.check")))
+            .clicked()
+        {
+            if let Some(library) = gdsfx.sfx_library.as_ref() {
+                gdsfx.outdated_sounds = Some(outdated_sounds(&library.sound_effects));
+            }
+        }
+        if let Some((done, total)) = progress {
+            ui.spinner();
+            ui.label(t!("outdated.redownloading", done = done, total = total));
+            ui.ctx().request_repaint();
+        }
+    });
+
+    let Some(sounds) = gdsfx.outdated_sounds.clone() else {
+        return;
+    };
+    if sounds.is_empty() {
+        ui.weak(t!("outdated.none"));
+        return;
+    }
+    ui.label(t!("outdated.found", count = sounds.len()));
+    egui::ScrollArea::vertical()
+        .id_source("outdated_files")
+        .max_height(150.0)
+        .show(ui, |ui| {
+            for sound in &sounds {
+                sfx_button(ui, gdsfx, sound);
+            }
+        });
+    if ui
+        .add_enabled(progress.is_none() && !is_offline(), Button::new(t!("outdated.redownload_all")))
+        .clicked()
+    {
+        gdsfx.confirm_redownload = true;
+    }
+}
+
+// re-downloading can mean fetching a large part of the library again
+fn redownload_dialog(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    if !gdsfx.confirm_redownload {
+        return;
+    }
+    let count = gdsfx.outdated_sounds.as_ref().map(Vec::len).unwrap_or(0);
+    let size: i64 = gdsfx.outdated_sounds.iter().flatten().map(|sound| sound.bytes()).sum();
+
+    let modal = Modal::new(ctx, "redownload_outdated");
+    modal.open();
+    modal.show(|ui| {
+        modal.title(ui, t!("bulk.confirm"));
+        modal.frame(ui, |ui| {
+            modal.body(ui, t!("outdated.confirm", count = count, size = convert(size as f64)));
+        });
+        modal.buttons(ui, |ui| {
+            if modal.button(ui, t!("common.cancel")).clicked() {
+                gdsfx.confirm_redownload = false;
+            }
+            if modal.suggested_button(ui, t!("outdated.redownload_all")).clicked() {
+                if let Some(sounds) = gdsfx.outdated_sounds.take() {
+                    redownload_sounds(sounds);
+                }
+                gdsfx.confirm_redownload = false;
+            }
+        });
+    });
+}
+
+// makes it explicit what happens to the files that are already downloaded
+fn category_download_dialog(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    let Some((id, mut redownload_existing)) = gdsfx.confirm_category_download else {
+        return;
+    };
+    let Some(category) = gdsfx.sfx_library.as_ref().and_then(|library| library.find_by_id(id)).cloned() else {
+        gdsfx.confirm_category_download = None;
+        return;
+    };
+    let (missing, existing) = category_download_counts(&category);
+    let (download, skip) = if redownload_existing { (missing + existing, 0) } else { (missing, existing) };
+
+    let modal = Modal::new(ctx, "confirm_category_download");
+    modal.open();
+    modal.show(|ui| {
+        modal.title(ui, t!("category.download_title", name = category.name()));
+        modal.frame(ui, |ui| {
+            modal.body(ui, t!("category.download_counts", download = download, skip = skip));
+            ui.add_enabled(existing > 0, egui::Checkbox::new(&mut redownload_existing, t!("category.redownload_existing")))
+                .on_hover_text(t!("category.redownload_existing_hint"));
+        });
+        modal.buttons(ui, |ui| {
+            if modal.button(ui, t!("common.cancel")).clicked() {
+                gdsfx.confirm_category_download = None;
+            } else if modal.suggested_button(ui, t!("category.download_all")).clicked() {
+                download_category(&category, redownload_existing);
+                gdsfx.confirm_category_download = None;
+            } else {
+                gdsfx.confirm_category_download = Some((id, redownload_existing));
+            }
+        });
+    });
+}
+
+// downloaded size over time, from the sizes recorded on every file scan
+fn cache_history_plot(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    let history = gdsfx.cache_history.get_or_insert_with(cache_history);
+    if history.len() < 2 {
+        ui.weak(t!("stats.cache_history_empty"));
+        return;
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    // x is in days relative to now, y in megabytes
+    let points = history
+        .iter()
+        .map(|(timestamp, _, bytes)| {
+            let days = (*timestamp as f64 - now as f64) / (60.0 * 60.0 * 24.0);
+            [days, *bytes as f64 / 1_000_000.0]
+        })
+        .collect::<egui_plot::PlotPoints>();
+
+    ui.label(t!("stats.cache_history"));
+    egui_plot::Plot::new("cache_history")
+        .height(150.0)
+        .allow_scroll(false)
+        .x_axis_label(t!("stats.days"))
+        .y_axis_label("MB")
+        .label_formatter(|_, point| format!("{:.1} MB", point.y))
+        .show(ui, |plot| plot.line(egui_plot::Line::new(points)));
+}
+
+fn stats_list(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    // (bytes, duration, files)
+    fn recursive(entry: &LibraryEntry) -> (u128, u128, i64) {
+        match <&CategoryEntry>::try_from(entry) {
+            Ok(category) => category
+                .children
+                .iter()
+                .map(recursive)
+                .reduce(|a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2))
+                .unwrap_or((0, 0, 1)),
+            Err(sound) => (sound.bytes as u128, sound.duration as u128, 1),
+        }
+    }
+    let (total_bytes, total_duration, total_files) =
+        recursive(&gdsfx.sfx_library.as_ref().unwrap().sound_effects);
+
+    ui.heading(t!("stats.library"));
+
+    ui.add_space(10.0);
+
+    ui.label(t!("stats.total_files", count = total_files));
+    ui.label(t!("stats.total_size", size = pretty_bytes::converter::convert(total_bytes as f64)));
+    ui.label(t!("stats.total_duration", duration = stringify_duration(total_duration as i64)));
+
+    ui.add_space(30.0);
+
+    ui.heading(t!("stats.files"));
+
+    ui.add_space(10.0);
+
+    ui.label(t!("stats.downloaded_files", count = EXISTING_SOUND_FILES.lock().unwrap().len()));
+
+    let (cached_sounds, cached_bytes) = sound_cache_size();
+    ui.label(t!("stats.sounds_in_memory", count = cached_sounds, size = convert(cached_bytes as f64)));
+    if let Some(library) = gdsfx.sfx_library.as_ref() {
+        ui.label(t!("stats.library_in_memory", count = library.index.len()));
+        if library.skipped_entries > 0 {
+            ui.label(t!("stats.skipped_entries", count = library.skipped_entries));
+        }
+        if !library.warnings.is_empty() {
+            egui::CollapsingHeader::new(t!("stats.library_warnings", count = library.warnings.len()))
+                .id_source("library_warnings")
+                .show(ui, |ui| {
+                    for warning in &library.warnings {
+                        ui.label(warning.message());
+                    }
+                });
+        }
+    }
+
+    let bandwidth = get_bandwidth();
+    ui.label(t!("stats.session_bandwidth", size = convert(session_bytes() as f64)));
+    ui.label(t!(
+        "stats.total_bandwidth",
+        size = convert(bandwidth.total_bytes as f64),
+        sessions = bandwidth.sessions,
+    ));
+
+    ui.add_space(10.0);
+
+    cache_history_plot(ui, gdsfx);
+
+    ui.add_space(10.0);
+
+    failed_downloads(ui, gdsfx);
+
+    ui.add_space(10.0);
+
+    outdated_files(ui, gdsfx);
+
+    orphaned_files_warning(ui, gdsfx);
+
+    ui.add_space(30.0);
+
+    ui.heading(t!("stats.level_usage"));
+
+    ui.add_space(10.0);
+
+    let (used_sounds, most_referenced) = usage_stats();
+    ui.label(t!("stats.sounds_used", count = used_sounds));
+    if let Some((level, count)) = most_referenced {
+        ui.label(t!("stats.most_referenced", level = level, count = count));
+    }
+
+    ui.add_space(30.0);
+
+    ui.heading(t!("stats.export"));
+
+    ui.add_space(10.0);
+
+    let path = gdsfx.outline_path.get_or_insert_with(|| {
+        GD_FOLDER
+            .join("gdsfx_library_outline.txt")
+            .to_string_lossy()
+            .to_string()
+    });
+    ui.label(t!("stats.outline_file"));
+    ui.text_edit_singleline(path);
+    ui.checkbox(&mut gdsfx.outline_options.include_ids, t!("stats.include_ids"));
+    ui.checkbox(&mut gdsfx.outline_options.include_sizes, t!("stats.include_sizes"));
+    if ui.button(t!("stats.export_outline")).clicked() {
+        let root = &gdsfx.sfx_library.as_ref().unwrap().sound_effects;
+        match export_library_outline(root, gdsfx.outline_options, path.as_ref()) {
+            Ok(()) => push_notification(t!("stats.exported_outline", path = path)),
+            Err(error) => push_error(t!("stats.export_outline_failed", error = error)),
+        }
+    }
+
+    ui.add_space(10.0);
+
+    let path = gdsfx.csv_path.get_or_insert_with(|| {
+        GD_FOLDER
+            .join("gdsfx_library.csv")
+            .to_string_lossy()
+            .to_string()
+    });
+    ui.label(t!("stats.csv_file"));
+    ui.text_edit_singleline(path);
+    if ui.button(t!("stats.export_csv")).clicked() {
+        let root = &gdsfx.sfx_library.as_ref().unwrap().sound_effects;
+        match export_library_csv(root, path.as_ref()) {
+            Ok(()) => push_notification(t!("stats.exported_csv", path = path)),
+            Err(error) => push_error(t!("stats.export_csv_failed", error = error)),
+        }
+    }
+
+    ui.add_space(10.0);
+
+    let history = get_play_history();
+    let path = gdsfx.play_history_path.get_or_insert_with(|| {
+        GD_FOLDER
+            .join("gdsfx_play_history.json")
+            .to_string_lossy()
+            .to_string()
+    });
+    ui.label(t!("stats.play_history_file", count = history.len()));
+    ui.text_edit_singleline(path);
+    if ui
+        .add_enabled(!history.is_empty(), Button::new(t!("stats.export_play_history")))
+        .clicked()
+    {
+        match export_play_history(&history, path.as_ref()) {
+            Ok(()) => push_notification(t!("stats.exported_play_history", path = path)),
+            Err(error) => push_error(t!("stats.export_play_history_failed", error = error)),
+        }
+    }
+}
+
+fn settings_list(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    let mut settings = get_settings();
+    let mut changed = false;
+    let mut recently_added_changed = false;
+
+    ui.heading(t!("stage.settings"));
+    ui.add_space(10.0);
+
+    changed |= ui
+        .checkbox(&mut settings.check_for_updates, t!("settings.check_for_updates"))
+        .changed();
+    changed |= ui
+        .checkbox(&mut settings.offline_mode, t!("settings.offline_mode"))
+        .changed();
+
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.version_check_interval"));
+        changed |= ui
+            .add(egui::DragValue::new(&mut settings.auto_version_check_interval_mins).suffix(t!("settings.minutes_suffix")))
+            .on_hover_text(t!("settings.zero_disables"))
+            .changed();
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.idle_unload"));
+        changed |= ui
+            .add(egui::DragValue::new(&mut settings.idle_unload_mins).suffix(t!("settings.minutes_suffix")))
+            .on_hover_text(t!("settings.idle_unload_hint"))
+            .changed();
+    });
+
+    changed |= ui
+        .checkbox(&mut settings.transliterate_search, t!("settings.transliterate_search"))
+        .on_hover_text(t!("settings.transliterate_search_hint"))
+        .changed();
+    changed |= ui
+        .checkbox(&mut settings.double_click_to_play, t!("settings.double_click_to_play"))
+        .changed();
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.replay_behavior"));
+        egui::ComboBox::from_id_source("replay_behavior")
+            .selected_text(settings.replay_behavior.name())
+            .show_ui(ui, |ui| {
+                for behavior in ReplayBehavior::iter() {
+                    changed |= ui
+                        .selectable_value(&mut settings.replay_behavior, behavior, behavior.name())
+                        .changed();
+                }
+            });
+    });
+    changed |= ui
+        .checkbox(&mut settings.auto_play_on_select, t!("settings.auto_play"))
+        .changed();
+    changed |= ui
+        .checkbox(&mut settings.favourite_on_download, t!("settings.favourite_on_download"))
+        .on_hover_text(t!("settings.favourite_on_download_hint"))
+        .changed();
+    changed |= ui
+        .checkbox(&mut settings.unfavourite_on_delete, t!("settings.unfavourite_on_delete"))
+        .changed();
+    changed |= ui
+        .checkbox(&mut settings.preview_on_hover, t!("settings.preview_on_hover"))
+        .changed();
+    ui.add_enabled_ui(settings.preview_on_hover, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(t!("settings.hover_preview_delay"));
+            changed |= ui
+                .add(egui::DragValue::new(&mut settings.hover_preview_delay_ms).suffix(" ms"))
+                .changed();
+        });
+    });
+
+    ui.horizontal(|ui| {
+        let mut limited = settings.preview_length_secs.is_some();
+        if ui.checkbox(&mut limited, t!("settings.limit_preview_length")).changed() {
+            settings.preview_length_secs = limited.then_some(3.0);
+            changed = true;
+        }
+        match settings.preview_length_secs.as_mut() {
+            Some(secs) => {
+                changed |= ui
+                    .add(egui::DragValue::new(secs).speed(0.1).clamp_range(0.1..=600.0).suffix("s"))
+                    .changed();
+            }
+            None => {
+                ui.weak(t!("settings.full_length"));
+            }
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.recently_added_prefix"));
+        let response = ui.add(egui::DragValue::new(&mut settings.recently_added_versions).clamp_range(0..=20));
+        ui.label(t!("settings.recently_added_suffix"));
+        if response.changed() {
+            changed = true;
+            recently_added_changed = true;
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.default_sorting"));
+        egui::ComboBox::from_id_source("default_sorting")
+            .selected_text(settings.default_sorting.to_string())
+            .show_ui(ui, |ui| {
+                for alternative in Sorting::iter() {
+                    changed |= ui
+                        .selectable_value(&mut settings.default_sorting, alternative, alternative.to_string())
+                        .changed();
+                }
+            });
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.language"));
+        egui::ComboBox::from_id_source("locale")
+            .selected_text(settings.locale.name())
+            .show_ui(ui, |ui| {
+                for locale in Locale::iter() {
+                    changed |= ui.selectable_value(&mut settings.locale, locale, locale.name()).changed();
+                }
+            });
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.result_layout"));
+        egui::ComboBox::from_id_source("result_layout")
+            .selected_text(settings.result_layout.name())
+            .show_ui(ui, |ui| {
+                for layout in ResultLayout::iter() {
+                    changed |= ui.selectable_value(&mut settings.result_layout, layout, layout.name()).changed();
+                }
+            });
+    });
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label(t!("settings.metadata_bar"));
+        for item in MetadataItem::iter() {
+            let mut shown = settings.metadata_bar.contains(&item);
+            if ui.checkbox(&mut shown, item.name()).changed() {
+                settings.metadata_bar.retain(|other| *other != item);
+                if shown {
+                    settings.metadata_bar.push(item);
+                }
+                changed = true;
+            }
+        }
+    })
+    .response
+    .on_hover_text(t!("settings.metadata_bar_hint"));
+
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.theme"));
+        egui::ComboBox::from_id_source("theme")
+            .selected_text(settings.theme.name())
+            .show_ui(ui, |ui| {
+                for theme in AppTheme::iter() {
+                    changed |= ui.selectable_value(&mut settings.theme, theme, theme.name()).changed();
+                }
+            });
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.detail_position"));
+        egui::ComboBox::from_id_source("detail_panel_position")
+            .selected_text(settings.detail_panel_position.name())
+            .show_ui(ui, |ui| {
+                for position in DetailPanelPosition::iter() {
+                    changed |= ui
+                        .selectable_value(&mut settings.detail_panel_position, position, position.name())
+                        .changed();
+                }
+            });
+    });
+
+    ui.add_space(10.0);
+    ui.label(t!("settings.window_size"));
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.window_minimum"));
+        changed |= ui
+            .add(egui::DragValue::new(&mut settings.min_window_width).clamp_range(100.0..=10000.0))
+            .changed();
+        ui.label("×");
+        changed |= ui
+            .add(egui::DragValue::new(&mut settings.min_window_height).clamp_range(100.0..=10000.0))
+            .changed();
+    });
+    ui.horizontal(|ui| {
+        let mut limited = settings.max_window_size.is_some();
+        if ui.checkbox(&mut limited, t!("settings.window_maximum")).changed() {
+            settings.max_window_size = limited.then_some((
+                settings.min_window_width.max(TOTAL_WIDTH) * 2.0,
+                settings.min_window_height.max(TOTAL_HEIGHT) * 2.0,
+            ));
+            changed = true;
+        }
+        match settings.max_window_size.as_mut() {
+            Some((width, height)) => {
+                changed |= ui.add(egui::DragValue::new(width).clamp_range(100.0..=10000.0)).changed();
+                ui.label("×");
+                changed |= ui.add(egui::DragValue::new(height).clamp_range(100.0..=10000.0)).changed();
+            }
+            None => {
+                ui.weak(t!("settings.unlimited"));
+            }
+        }
+    });
+
+    if ui.button(if gdsfx.fullscreen { t!("settings.exit_fullscreen") } else { t!("settings.toggle_fullscreen") })
+        .on_hover_text(KeyBindings::format(ui.ctx(), &gdsfx.key_bindings.toggle_fullscreen))
+        .clicked()
+    {
+        gdsfx.toggle_fullscreen(ui.ctx());
+    }
+
+    if changed {
+        set_settings(settings);
+    }
+    if recently_added_changed {
+        gdsfx.update_recently_added();
+    }
+
+    ui.add_space(20.0);
+
+    color_rules_editor(ui);
+
+    ui.add_space(20.0);
+
+    tag_editor(ui, gdsfx);
+
+    ui.add_space(20.0);
+
+    hidden_list(ui, gdsfx);
+
+    ui.add_space(20.0);
+
+    annotations_settings(ui, gdsfx);
+
+    ui.add_space(20.0);
+
+    if ui.button(t!("settings.copy_diagnostics")).clicked() {
+        let report = diagnostics_report(gdsfx.sfx_version, gdsfx.cdn_url.as_deref());
+        ui.output_mut(|o| o.copied_text = report);
+        push_notification(t!("settings.copied_diagnostics"));
+    }
+
+    if ui.button(t!("settings.test_audio")).clicked() {
+        gdsfx.audio_test_result = Some(test_audio());
+    }
+    match &gdsfx.audio_test_result {
+        Some(Ok(())) => {
+            ui.label(t!("settings.test_audio_playing"));
+        }
+        Some(Err(error)) => {
+            ui.colored_label(ui.visuals().error_fg_color, t!("settings.audio_error", error = error));
+        }
+        None => {}
+    }
+}
+
+fn color_rules_editor(ui: &mut Ui) {
+    let mut rules = get_color_rules();
+    let mut changed = false;
+    let mut remove = None;
+    let mut move_up = None;
+
+    ui.label(t!("color_rules.title"));
+    ui.weak(t!("color_rules.help", tag_prefix = TAG_PREFIX));
+    for (index, rule) in rules.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            changed |= ui.color_edit_button_srgba(&mut rule.color).changed();
+            changed |= ui.text_edit_singleline(&mut rule.pattern).changed();
+            if ui.add_enabled(index > 0, Button::new("⏶").small()).on_hover_text(t!("color_rules.move_up")).clicked() {
+                move_up = Some(index);
+            }
+            if ui.small_button("❌").on_hover_text(t!("color_rules.remove")).clicked() {
+                remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = move_up {
+        rules.swap(index - 1, index);
+        changed = true;
+    }
+    if let Some(index) = remove {
+        rules.remove(index);
+        changed = true;
+    }
+    if ui.button(t!("color_rules.add")).clicked() {
+        rules.push(ColorRule { pattern: String::new(), color: egui::Color32::LIGHT_BLUE });
+        changed = true;
+    }
+
+    if changed {
+        set_color_rules(rules);
+    }
+}
+
+fn annotations_settings(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    let mut settings = get_settings();
+    let mut changed = false;
+
+    ui.label(t!("annotations.title"));
+    ui.horizontal(|ui| {
+        ui.label(t!("annotations.url"));
+        changed |= ui.text_edit_singleline(&mut settings.community_annotations_url).changed();
+    });
+    ui.horizontal(|ui| {
+        ui.label(t!("annotations.refetch_after"));
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut settings.annotations_refetch_hours)
+                    .clamp_range(0..=MAX_ANNOTATIONS_REFETCH_HOURS)
+                    .suffix(" h"),
+            )
+            .on_hover_text(t!("annotations.refetch_hint"))
+            .changed();
+    });
+    if changed {
+        set_settings(settings);
+    }
+
+    ui.horizontal(|ui| {
+        let fetching = gdsfx.annotations_fetch.is_some();
+        let has_url = !get_settings().community_annotations_url.trim().is_empty();
+        if ui
+            .add_enabled(!fetching && has_url, Button::new(t!("annotations.import")))
+            .on_disabled_hover_text(t!("annotations.no_url"))
+            .clicked()
+        {
+            gdsfx.fetch_annotations();
+        }
+        if fetching {
+            ui.spinner();
+        }
+    });
+}
+
+fn annotations_dialog(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    let Some(diff) = gdsfx.annotations_preview.clone() else {
+        return;
+    };
+    let name = |id: i64| {
+        gdsfx
+            .sfx_library
+            .as_ref()
+            .and_then(|library| library.find_by_id(id))
+            .map(|sound| sound.name().to_string())
+            .unwrap_or_else(|| id.to_string())
+    };
+    let changes = diff
+        .tags
+        .iter()
+        .map(|(id, tag)| format!("{}: + {tag}", name(*id)))
+        .chain(diff.ratings.iter().map(|(id, rating)| {
+            format!("{}: {}", name(*id), "★".repeat(*rating as usize))
+        }))
+        .collect::<Vec<_>>();
+
+    let modal = Modal::new(ctx, "annotations_preview");
+    modal.open();
+    modal.show(|ui| {
+        modal.title(ui, t!("annotations.preview"));
+        modal.frame(ui, |ui| {
+            modal.body(ui, t!("annotations.summary", tags = diff.tags.len(), ratings = diff.ratings.len()));
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                for change in changes {
+                    ui.label(change);
+                }
+            });
+        });
+        modal.buttons(ui, |ui| {
+            if modal.button(ui, t!("common.cancel")).clicked() {
+                gdsfx.annotations_preview = None;
+            }
+            if modal.suggested_button(ui, t!("common.apply")).clicked() {
+                diff.apply();
+                gdsfx.annotations_preview = None;
+            }
+        });
+    });
+}
+
+fn hidden_list(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    let hidden = get_hidden();
+    ui.label(t!("hidden.title", count = hidden.len()));
+    if hidden.is_empty() {
+        ui.weak(t!("hidden.empty"));
+        return;
+    }
+    egui::ScrollArea::vertical()
+        .id_source("hidden_list")
+        .max_height(150.0)
+        .show(ui, |ui| {
+            for id in hidden {
+                ui.horizontal(|ui| {
+                    let entry = gdsfx.sfx_library.as_ref().and_then(|library| library.find_by_id(id));
+                    match entry {
+                        Some(entry) if entry.is_category() => ui.label(t!("hidden.category", name = entry.name())),
+                        Some(entry) => ui.label(entry.name()),
+                        // not in this library version, but kept in case it comes back
+                        None => ui.weak(t!("hidden.unknown", id = id)),
+                    };
+                    if ui.small_button(t!("hidden.unhide")).clicked() {
+                        unhide(id);
+                    }
+                });
+            }
+        });
+}
+
+fn tag_editor(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    let mut palette = palette_tags();
+    let mut changed = false;
+    let mut delete = None;
+    let mut rename = None;
+
+    ui.label(t!("tags.title"));
+    for tag in palette.iter_mut() {
+        ui.horizontal(|ui| {
+            changed |= ui.color_edit_button_srgba(&mut tag.color).changed();
+            match gdsfx.tag_rename.as_mut() {
+                Some((old, new)) if *old == tag.name => {
+                    let response = ui.text_edit_singleline(new);
+                    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if submitted || ui.small_button("✔").clicked() {
+                        rename = Some((old.clone(), new.trim().to_string()));
+                    }
+                }
+                _ => {
+                    ui.label(&tag.name);
+                    if ui.small_button("✏").on_hover_text(t!("tags.rename")).clicked() {
+                        gdsfx.tag_rename = Some((tag.name.clone(), tag.name.clone()));
+                    }
+                }
+            }
+            if ui.small_button("❌").on_hover_text(t!("tags.delete_hint")).clicked() {
+                delete = Some(tag.name.clone());
+            }
+        });
+    }
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut gdsfx.new_palette_tag);
+        let name = gdsfx.new_palette_tag.trim().to_string();
+        let valid = !name.is_empty() && !palette.iter().any(|tag| tag.name == name);
+        if ui.add_enabled(valid, Button::new(t!("tags.add"))).clicked() {
+            palette.push(PaletteTag { name, color: DEFAULT_TAG_COLOR });
+            gdsfx.new_palette_tag.clear();
+            changed = true;
+        }
+    });
+
+    // colors are stored for every tag once anything is edited
+    if changed {
+        set_palette(palette);
+    }
+    if let Some((old, new)) = rename {
+        if !new.is_empty() && new != old {
+            rename_tag(&old, &new);
+            if gdsfx.filters.tag.as_ref() == Some(&old) {
+                gdsfx.filters.tag = Some(new);
+            }
+        }
+        gdsfx.tag_rename = None;
+    }
+    if let Some(name) = delete {
+        delete_tag(&name);
+        if gdsfx.filters.tag.as_ref() == Some(&name) {
+            gdsfx.filters.tag = None;
+        }
+    }
+}
+
+fn credits_list(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    ui.heading(t!("credits.title"));
+    ui.add_space(10.0);
+    let query = fold_case(&gdsfx.credits_query);
+    let credits = gdsfx
+        .sfx_library
+        .as_ref()
+        .unwrap()
+        .credits
+        .iter()
+        .filter(|credits| {
+            name_matches(&credits.name, &query) || fold_case(&credits.link).contains(&query)
+        })
+        .collect::<Vec<_>>();
+    if credits.is_empty() {
+        ui.label(t!("credits.no_matches", query = gdsfx.credits_query));
+    }
+    for credits in credits {
+        ui.hyperlink_to(&credits.name, &credits.link);
+    }
+
+    ui.add_space(30.0);
+
+    ui.heading(t!("credits.this_project"));
+    ui.hyperlink_to("GitHub", "https://github.com/SpeckyYT/gd_sfx");
+    ui.add_space(10.0);
+
+    for (name, link) in [
+        ("Specky", "https://github.com/SpeckyYT"),
+        ("tags", "https://github.com/zTags"),
+        ("kr8gz", "https://github.com/kr8gz"),
+    ] {
+        ui.hyperlink_to(name, link);
+    }
+}
+
+fn search_bar(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    ui.heading(t!("search.title"));
+    ui.horizontal(|ui| {
+        if gdsfx.stage == Stage::Credits {
+            ui.text_edit_singleline(&mut gdsfx.credits_query);
+            return;
+        }
+        autocomplete_search_field(ui, gdsfx);
+        random_button(ui, gdsfx);
+        if gdsfx.is_searching() {
+            ui.spinner();
+        }
+    });
+    if gdsfx.stage == Stage::Credits {
+        return;
+    }
+    pasted_ids_bar(ui, gdsfx);
+    ui.horizontal(|ui| {
+        let mut fuzzy = gdsfx.fuzziness.is_some();
+        if ui
+            .checkbox(&mut fuzzy, t!("search.fuzzy"))
+            .on_hover_text(t!("search.fuzzy_hint"))
+            .changed()
+        {
+            gdsfx.fuzziness = fuzzy.then_some(DEFAULT_FUZZINESS);
+        }
+        ui.checkbox(&mut gdsfx.match_categories, t!("search.match_categories"))
+            .on_hover_text(t!("search.match_categories_hint"));
+        if let Some(fuzziness) = gdsfx.fuzziness.as_mut() {
+            ui.add(
+                egui::Slider::new(fuzziness, 0.0..=MAX_FUZZINESS)
+                    .text(t!("search.fuzziness"))
+                    .custom_formatter(|value, _| format!("{:.0}%", value * 100.0)),
+            );
+        }
+    });
+}
+
+// only ids of sounds in the library count, so unrelated numbers mostly drop out
+fn detect_pasted_ids(gdsfx: &mut GdSfx, text: &str, query_before: &str) {
+    let Some(library) = gdsfx.sfx_library.as_ref() else {
+        return;
+    };
+    let ids = sfx_ids_in_text(text)
+        .into_iter()
+        .filter(|id| library.find_by_id(*id).is_some_and(LibraryEntry::is_sound))
+        .collect::<Vec<_>>();
+    if !ids.is_empty() {
+        gdsfx.pasted_ids = Some((query_before.to_string(), ids));
+    }
+}
+
+// what to do with the ids found in pasted text, each choice puts the old query back
+fn pasted_ids_bar(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    let Some((query, ids)) = gdsfx.pasted_ids.clone() else {
+        return;
+    };
+    let mut done = false;
+    ui.horizontal_wrapped(|ui| {
+        match ids.as_slice() {
+            [id] => {
+                let sound = gdsfx.sfx_library.as_ref().and_then(|library| library.find_by_id(*id)).cloned();
+                if let Some(sound) = sound {
+                    ui.label(t!("paste.found_one", name = sound.name(), id = id));
+                    if ui.button(t!("paste.go_to")).clicked() {
+                        select_and_reveal(gdsfx, &sound);
+                        gdsfx.queue_auto_play();
+                        gdsfx.stage = Stage::Library;
+                        done = true;
+                    }
+                }
+            }
+            _ => {
+                ui.label(t!("paste.found_many", count = ids.len()));
+                if ui.button(t!("paste.select_all")).clicked() {
+                    gdsfx.multi_select = ids.iter().copied().collect();
+                    done = true;
+                }
+                if ui.button(t!("paste.filter")).clicked() {
+                    gdsfx.filters.ids = ids.clone();
+                    done = true;
+                }
+            }
+        }
+        if done {
+            gdsfx.search_query = query;
+        }
+        if ui.small_button(t!("paste.dismiss")).clicked() {
+            done = true;
+        }
+    });
+    if done {
+        gdsfx.pasted_ids = None;
+    }
+}
+
+fn autocomplete_search_field(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    let popup_id = egui::Id::new("search_autocomplete");
+    let suggestions = autocomplete_suggestions(gdsfx);
+
+    // the arrow keys have to be taken before the text field moves its cursor with them
+    if ui.memory(|mem| mem.is_popup_open(popup_id)) && !suggestions.is_empty() {
+        let (up, down) = ui.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            )
+        });
+        if down {
+            gdsfx.autocomplete_index = Some(match gdsfx.autocomplete_index {
+                Some(index) => (index + 1).min(suggestions.len() - 1),
+                None => 0,
+            });
+        }
+        if up {
+            gdsfx.autocomplete_index = gdsfx.autocomplete_index.and_then(|index| index.checked_sub(1));
+        }
+    }
+
+    let query_before = gdsfx.search_query.clone();
+    let response = ui.text_edit_singleline(&mut gdsfx.search_query);
+    if response.has_focus() {
+        let pasted = ui.input(|i| {
+            i.events
+                .iter()
+                .filter_map(|event| match event {
+                    egui::Event::Paste(text) => Some(text.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        });
+        for text in pasted {
+            detect_pasted_ids(gdsfx, &text, &query_before);
+        }
+    }
+    if response.changed() {
+        gdsfx.autocomplete_index = None;
+    }
+    if response.gained_focus() || response.changed() {
+        ui.memory_mut(|mem| mem.open_popup(popup_id));
+    }
+
+    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+        let selected = gdsfx
+            .autocomplete_index
+            .and_then(|index| suggestions.get(index))
+            .map(|(suggestion, _)| suggestion.clone());
+        complete_search(ui, gdsfx, selected.unwrap_or_else(|| gdsfx.search_query.clone()));
+        return;
+    }
+
+    if suggestions.is_empty() {
+        return;
+    }
+    let mut clicked = None;
+    egui::popup_below_widget(ui, popup_id, &response, |ui| {
+        for (i, (suggestion, from_history)) in suggestions.iter().enumerate() {
+            let text = if *from_history {
+                format!("🕘 {suggestion}")
+            } else {
+                suggestion.clone()
+            };
+            if ui.selectable_label(gdsfx.autocomplete_index == Some(i), text).clicked() {
+                clicked = Some(suggestion.clone());
+            }
+        }
+    });
+    if let Some(suggestion) = clicked {
+        complete_search(ui, gdsfx, suggestion);
+    }
+}
+
+fn complete_search(ui: &mut Ui, gdsfx: &mut GdSfx, query: String) {
+    record_search(&query);
+    gdsfx.search_query = query;
+    gdsfx.autocomplete = None;
+    gdsfx.autocomplete_index = None;
+    ui.memory_mut(|mem| mem.close_popup());
+}
+
+// previous searches first, then sound names starting with the query
+fn autocomplete_suggestions(gdsfx: &mut GdSfx) -> Vec<(String, bool)> {
+    if let Some((query, suggestions)) = gdsfx.autocomplete.as_ref() {
+        if *query == gdsfx.search_query {
+            return suggestions.clone();
+        }
+    }
+    let query = fold_case(&gdsfx.search_query);
+
+    let mut suggestions = get_search_history()
+        .into_iter()
+        .filter(|previous| {
+            let previous = fold_case(previous);
+            previous.starts_with(&query) && previous != query
+        })
+        .map(|previous| (previous, true))
+        .collect::<Vec<_>>();
+
+    if !query.is_empty() {
+        if let Some(library) = gdsfx.sfx_library.as_ref() {
+            let mut names = library
+                .sound_effects
+                .sounds()
+                .into_iter()
+                .map(|sound| sound.name())
+                .filter(|name| {
+                    let name = fold_case(name);
+                    name.starts_with(&query) && name != query
+                })
+                .collect::<Vec<_>>();
+            names.sort();
+            names.dedup();
+            for name in names {
+                if !suggestions.iter().any(|(suggestion, _)| suggestion == name) {
+                    suggestions.push((name.to_string(), false));
+                }
+            }
+        }
+    }
+    suggestions.truncate(AUTOCOMPLETE_SUGGESTIONS);
+
+    gdsfx.autocomplete = Some((gdsfx.search_query.clone(), suggestions.clone()));
+    suggestions
+}
+
+// sounds currently shown in the list of the active stage
+fn visible_sounds(gdsfx: &GdSfx) -> Vec<LibraryEntry> {
+    let Some((_, sfx)) = gdsfx.search_results.as_ref() else {
+        return vec![];
+    };
+    let Some(root) = sfx.first() else {
+        return vec![];
+    };
+    root.sounds()
+        .into_iter()
+        .filter(|sound| gdsfx.stage != Stage::Favourites || sound.is_favourite())
+        .cloned()
+        .collect()
+}
+
+fn random_button(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    let sounds = visible_sounds(gdsfx);
+    let response = ui
+        .add_enabled(!sounds.is_empty(), Button::new(t!("search.random")))
+        .on_disabled_hover_text(t!("search.random_disabled"));
+    if response.clicked() {
+        if let Some(sound) = sounds.choose(&mut rand::thread_rng()) {
+            select_and_reveal(gdsfx, sound);
+            stop_audio();
+            play_sound(sound, CDN_URL);
+        }
+    }
+}
+
+// selects the sound, expands its categories and scrolls the list to it
+fn select_and_reveal(gdsfx: &mut GdSfx, sound: &LibraryEntry) {
+    if let Some(library) = gdsfx.sfx_library.as_ref() {
+        for entry in library.path_to(sound.id()) {
+            if entry.is_category() {
+                gdsfx.category_open.insert(entry.id(), true);
+            }
+        }
+    }
+    gdsfx.revealed_categories.insert(sound.parent());
+    gdsfx.selected_sfx = Some(sound.clone());
+    gdsfx.scroll_to_sfx = Some(sound.id());
+}
+
+fn go_to_id_window(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    let Some((input, error)) = gdsfx.go_to_id.as_mut() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut target = None;
+    egui::Window::new(t!("go_to_id.title"))
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            let response = ui.add(egui::TextEdit::singleline(input).hint_text(t!("go_to_id.hint")));
+            response.request_focus();
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                let sound = input.trim().parse::<i64>().ok().and_then(|id| {
+                    gdsfx.sfx_library.as_ref()?.find_by_id(id).filter(|e| e.is_sound()).cloned()
+                });
+                match sound {
+                    Some(sound) => target = Some(sound),
+                    None => *error = Some(t!("go_to_id.not_found", id = input.trim())),
+                }
+            }
+            if let Some(error) = error {
+                ui.colored_label(ui.visuals().error_fg_color, error.as_str());
+            }
+        });
+
+    if let Some(sound) = target {
+        gdsfx.stage = Stage::Library;
+        // make sure the sound isn't hidden by the current search,
+        // results of an older search still being replaced can't tell
+        let key = gdsfx.search_key();
+        let current = gdsfx.search_results.as_ref().is_some_and(|(results_key, _)| *results_key == key);
+        if !current || !visible_sounds(gdsfx).iter().any(|s| s.id() == sound.id()) {
+            gdsfx.search_query.clear();
+            gdsfx.filters = Filters::default();
+        }
+        select_and_reveal(gdsfx, &sound);
+        gdsfx.queue_auto_play();
+        gdsfx.go_to_id = None;
+    } else if !open {
+        gdsfx.go_to_id = None;
+    }
+}
+
+fn sort_menu(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    ui.menu_button(t!("sorting.title"), |ui| {
+        for alternative in Sorting::iter() {
+            let response = ui.radio_value(&mut gdsfx.sorting, alternative, alternative.to_string());
+            if response.clicked() {
+                ui.close_menu();
+            }
+        }
+    });
+}
+
+fn filter_menu(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    fn optional_value(ui: &mut Ui, text: &str, value: &mut Option<f64>, default: f64, suffix: &str) {
+        ui.horizontal(|ui| {
+            let mut enabled = value.is_some();
+            if ui.checkbox(&mut enabled, text).changed() {
+                *value = enabled.then_some(default);
+            }
+            if let Some(value) = value {
+                ui.add(
+                    egui::DragValue::new(value)
+                        .speed(0.1)
+                        .clamp_range(0.0..=f64::MAX)
+                        .suffix(suffix),
+                );
+            }
+        });
+    }
+
+    ui.menu_button(t!("filters.title"), |ui| {
+        let filters = &mut gdsfx.filters;
+        ui.checkbox(&mut filters.favourites_only, t!("filters.favourites_only"));
+        ui.checkbox(&mut filters.downloaded_only, t!("filters.downloaded_only"));
+        ui.checkbox(&mut filters.show_hidden, t!("filters.show_hidden"));
+        ui.separator();
+        optional_value(ui, t!("filters.min_duration"), &mut filters.min_duration, 0.0, "s");
+        optional_value(ui, t!("filters.max_duration"), &mut filters.max_duration, 1.0, "s");
+        optional_value(ui, t!("filters.min_size"), &mut filters.min_size, 0.0, " kB");
+        optional_value(ui, t!("filters.max_size"), &mut filters.max_size, 100.0, " kB");
+        let collections = get_collections();
+        if !collections.is_empty() {
+            ui.separator();
+            egui::ComboBox::from_label(t!("filters.collection_label"))
+                .selected_text(filters.collection.clone().unwrap_or_else(|| t!("filters.any_collection").to_string()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut filters.collection, None, t!("filters.any_collection"));
+                    for collection in collections {
+                        let name = Some(collection.name.clone());
+                        ui.selectable_value(&mut filters.collection, name, collection.name);
+                    }
+                });
+        }
+    });
+}
+
+fn preset_menu(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    ui.menu_button(t!("presets.title"), |ui| {
+        let path = gdsfx.preset_path.get_or_insert_with(|| {
+            GD_FOLDER
+                .join("gdsfx_preset.json")
+                .to_string_lossy()
+                .to_string()
+        });
+        ui.label(t!("presets.file"));
+        ui.text_edit_singleline(path);
+        let path = path.clone();
+        ui.horizontal(|ui| {
+            if ui.button(t!("presets.save")).clicked() {
+                let preset = ViewPreset {
+                    query: gdsfx.search_query.clone(),
+                    sorting: gdsfx.sorting,
+                    filters: gdsfx.filters.clone(),
+                };
+                match save_preset(&preset, path.as_ref()) {
+                    Ok(()) => push_notification(t!("presets.saved", path = path)),
+                    Err(error) => push_error(t!("presets.save_failed", error = error)),
+                }
+                ui.close_menu();
+            }
+            if ui.button(t!("presets.load")).clicked() {
+                match load_preset(path.as_ref()) {
+                    Ok(preset) => {
+                        gdsfx.search_query = preset.query;
+                        gdsfx.sorting = preset.sorting;
+                        gdsfx.filters = preset.filters;
+                        gdsfx.stage = Stage::Library;
+                        push_notification(t!("presets.loaded", path = path));
+                    }
+                    Err(error) => push_error(t!("presets.load_failed", error = error)),
+                }
+                ui.close_menu();
+            }
+        });
+    });
+}
+
+fn filter_chips(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    if gdsfx.filters.is_empty() {
+        return;
+    }
+
+    fn chip(ui: &mut Ui, text: String) -> bool {
+        ui.add(Button::new(format!("{text} ❌")).small().rounding(8.0))
+            .on_hover_text(t!("filters.remove"))
+            .clicked()
+    }
+
+    let filters = &mut gdsfx.filters;
+    let library = &gdsfx.sfx_library;
+    ui.horizontal_wrapped(|ui| {
+        if filters.favourites_only && chip(ui, t!("filters.favourites").to_string()) {
+            filters.favourites_only = false;
+        }
+        if filters.downloaded_only && chip(ui, t!("filters.downloaded").to_string()) {
+            filters.downloaded_only = false;
+        }
+        if filters.show_hidden && chip(ui, t!("filters.hidden").to_string()) {
+            filters.show_hidden = false;
+        }
+        if let Some(min) = filters.min_duration {
+            if chip(ui, format!("≥ {min:.1}s")) {
+                filters.min_duration = None;
+            }
+        }
+        if let Some(max) = filters.max_duration {
+            if chip(ui, format!("≤ {max:.1}s")) {
+                filters.max_duration = None;
+            }
+        }
+        if let Some(min) = filters.min_size {
+            if chip(ui, format!("≥ {min:.1} kB")) {
+                filters.min_size = None;
+            }
+        }
+        if let Some(max) = filters.max_size {
+            if chip(ui, format!("≤ {max:.1} kB")) {
+                filters.max_size = None;
+            }
+        }
+        for id in filters.categories.clone() {
+            let name = library
+                .as_ref()
+                .and_then(|library| library.find_by_id(id))
+                .map(|category| category.name().to_string())
+                .unwrap_or_else(|| id.to_string());
+            if chip(ui, t!("filters.category", name = name)) {
+                filters.categories.retain(|category| *category != id);
+            }
+        }
+        if let Some(tag) = filters.tag.clone() {
+            if chip(ui, t!("filters.tag", tag = tag)) {
+                filters.tag = None;
+            }
+        }
+        if let Some(collection) = filters.collection.clone() {
+            if chip(ui, t!("filters.collection", name = collection)) {
+                filters.collection = None;
+            }
+        }
+        if !filters.ids.is_empty() && chip(ui, t!("filters.ids", count = filters.ids.len())) {
+            filters.ids.clear();
+        }
+        if ui.small_button(t!("filters.clear_all")).clicked() {
+            *filters = Filters::default();
+        }
+    });
+}
+
+fn help_window(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    egui::Window::new(t!("help.title"))
+        .open(&mut gdsfx.show_help)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("key_bindings")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong(t!("help.action"));
+                    ui.strong(t!("help.keys"));
+                    ui.end_row();
+                    for (action, shortcut) in gdsfx.key_bindings.list() {
+                        ui.label(action);
+                        ui.label(KeyBindings::format(ctx, shortcut));
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+fn bulk_actions(ui: &mut Ui, gdsfx: &mut GdSfx) {
+    let ids = visible_sounds(gdsfx).iter().map(|sound| sound.id()).collect::<Vec<_>>();
+
+    ui.horizontal(|ui| {
+        ui.label(t!("bulk.results", count = ids.len()));
+        ui.add_enabled_ui(!ids.is_empty(), |ui| {
+            ui.menu_button(t!("bulk.title"), |ui| {
+                if ui.button(t!("bulk.favourite_all")).clicked() {
+                    request_bulk_action(ui.ctx(), gdsfx, BulkAction::Favourite(ids.clone()));
+                    ui.close_menu();
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut gdsfx.bulk_tag).hint_text(t!("bulk.tag_hint")));
+                    let tag = gdsfx.bulk_tag.trim().to_string();
+                    if ui.add_enabled(!tag.is_empty(), Button::new(t!("bulk.tag_all"))).clicked() {
+                        request_bulk_action(ui.ctx(), gdsfx, BulkAction::Tag(ids.clone(), tag));
+                        gdsfx.bulk_tag.clear();
+                        ui.close_menu();
+                    }
+                });
+                ui.horizontal_wrapped(|ui| {
+                    for tag in all_tags() {
+                        if ui.small_button(&tag).on_hover_text(t!("bulk.tag_all")).clicked() {
+                            request_bulk_action(ui.ctx(), gdsfx, BulkAction::Tag(ids.clone(), tag));
+                            ui.close_menu();
+                        }
+                    }
+                });
+            });
+        });
+        if !gdsfx.multi_select.is_empty() {
+            ui.label(t!("multi_select.count", count = gdsfx.multi_select.len()));
+            if ui.small_button(t!("multi_select.clear")).clicked() {
+                gdsfx.multi_select.clear();
+            }
+        }
+        if let Some((_, tag)) = gdsfx.tag_undo.as_ref() {
+            if ui
+                .small_button(t!("tags.undo"))
+                .on_hover_text(t!("tags.undo_hint", tag = tag))
+                .clicked()
+            {
+                gdsfx.undo_tags();
+            }
+        }
+    });
+}
+
+fn request_bulk_action(ctx: &egui::Context, gdsfx: &mut GdSfx, action: BulkAction) {
+    let count = match &action {
+        BulkAction::Favourite(ids) | BulkAction::Tag(ids, _) => ids.len(),
+    };
+    if count > BULK_CONFIRM_THRESHOLD {
+        gdsfx.pending_bulk_action = Some(action);
+    } else {
+        apply_bulk_action(ctx, gdsfx, action);
+    }
+}
+
+fn apply_bulk_action(ctx: &egui::Context, gdsfx: &mut GdSfx, action: BulkAction) {
+    match action {
+        BulkAction::Favourite(ids) => add_favourites(&ids),
+        BulkAction::Tag(ids, tag) => gdsfx.tag_sounds(ctx, &ids, tag),
+    }
+}
+
+fn bulk_action_dialog(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    let Some(action) = gdsfx.pending_bulk_action.clone() else {
+        return;
+    };
+
+    let text = match &action {
+        BulkAction::Favourite(ids) => t!("bulk.confirm_favourite", count = ids.len()),
+        BulkAction::Tag(ids, tag) => t!("bulk.confirm_tag", count = ids.len(), tag = tag),
+    };
+
+    let modal = Modal::new(ctx, "bulk_action");
+    modal.open();
+    modal.show(|ui| {
+        modal.title(ui, t!("bulk.confirm"));
+        modal.frame(ui, |ui| {
+            modal.body(ui, text);
+        });
+        modal.buttons(ui, |ui| {
+            if modal.button(ui, t!("common.cancel")).clicked() {
+                gdsfx.pending_bulk_action = None;
+            }
+            if modal.suggested_button(ui, t!("common.apply")).clicked() {
+                apply_bulk_action(ui.ctx(), gdsfx, action);
+                gdsfx.pending_bulk_action = None;
+            }
+        });
+    });
+}
+
+fn copy_trigger_snippet(ui: &mut Ui, entry: &LibraryEntry) {
+    let snippet = sfx_trigger_snippet(entry);
+    ui.output_mut(|o| o.copied_text = snippet);
+    push_notification(t!("sound.copied_trigger", name = entry.name()));
+}
+
+fn sfx_button(ui: &mut Ui, gdsfx: &mut GdSfx, entry: &LibraryEntry) {
+    let text = if is_used(entry.id()) {
+        format!("{} {USED_CHARACTER}", entry.pretty_name())
+    } else {
+        entry.pretty_name()
+    };
+    let text = if is_downloading(entry.id()) {
+        ui.ctx().request_repaint();
+        format!("{text} ⏳")
+    } else {
+        text
+    };
+    let mut text = egui::RichText::new(text);
+    if let Some(color) = sound_color(entry) {
+        text = text.color(color);
+    } else if let Some(color) = current_theme().downloaded_color().filter(|_| entry.exists()) {
+        text = text.color(color);
+    }
+    let sound = ui
+        .add(Button::new(text).selected(gdsfx.multi_select.contains(&entry.id())))
+        .interact(egui::Sense::click_and_drag());
+    // revealing a sound scrolls to where it really is
+    if gdsfx.scroll_to_sfx == Some(entry.id()) && !gdsfx.in_virtual_category {
+        sound.scroll_to_me(Some(egui::Align::Center));
+        gdsfx.scroll_to_sfx = None;
+    }
+    if sound.drag_started() {
+        gdsfx.dragged_sound = Some(entry.clone()); // can be dropped onto the tag palette
+        // egui can't drag data out of the window, so the clipboard is used instead
+        let text = if entry.is_custom() {
+            format!("{} (ID: {})", entry.name(), entry.id())
+        } else {
+            format!("{} (ID: {}) {}", entry.name(), entry.id(), sfx_url(CDN_URL, entry))
+        };
+        ui.output_mut(|o| o.copied_text = text);
+        push_notification(t!("sound.copied", name = entry.name()));
+    }
+    if sound.hovered() {
+        gdsfx.selected_sfx = Some(entry.clone());
+        hover_preview(ui, gdsfx, entry);
+    }
+    let toggle_selection = sound.clicked() && ui.input(|i| i.modifiers.command);
+    if toggle_selection && !gdsfx.multi_select.remove(&entry.id()) {
+        gdsfx.multi_select.insert(entry.id());
+    }
+    let settings = get_settings();
+    let play = if toggle_selection {
+        false
+    } else if settings.double_click_to_play {
+        if sound.clicked() {
+            gdsfx.selected_sfx = Some(entry.clone());
+            gdsfx.queue_auto_play();
+        }
+        sound.double_clicked()
+    } else {
+        sound.clicked()
+    };
+    if play {
+        gdsfx.pending_auto_play = None; // the double click already plays it
+        let playing = now_playing() == Some(entry.id());
+        match settings.replay_behavior {
+            ReplayBehavior::Toggle if playing => stop_audio(),
+            ReplayBehavior::Ignore if playing => {}
+            _ => {
+                stop_audio();
+                play_sound(entry, CDN_URL);
+            }
+        }
+    }
+    sound.context_menu(|ui| {
+        if !gdsfx.multi_select.is_empty() {
+            ui.menu_button(t!("multi_select.apply_tag", count = gdsfx.multi_select.len()), |ui| {
+                let palette = palette_tags();
+                if palette.is_empty() {
+                    ui.weak(t!("multi_select.no_tags"));
+                }
+                for tag in palette {
+                    if ui.button(&tag.name).clicked() {
+                        let ids = gdsfx.multi_select.drain().collect::<Vec<_>>();
+                        gdsfx.tag_sounds(ui.ctx(), &ids, tag.name);
+                        ui.close_menu();
+                    }
+                }
+            });
+            if ui.button(t!("multi_select.clear")).clicked() {
+                gdsfx.multi_select.clear();
+                ui.close_menu();
+            }
+            ui.separator();
+        }
+        if has_favourite(entry.id()) {
+            if ui.button(t!("sound.remove_favourite")).clicked() {
+                remove_favourite(entry.id());
+                ui.close_menu();
+            }
+        } else if ui.button(t!("sound.favourite")).clicked() {
+            favourite_sound(gdsfx, entry);
+            ui.close_menu();
+        }
+        hide_button(ui, entry);
+        if !entry.exists()
+            && !entry.is_custom()
+            && ui
+                .add_enabled(!is_offline() && !is_downloading(entry.id()), Button::new(t!("sound.download_and_play")))
+                .clicked()
+        {
+            download_and_play(entry);
+            ui.close_menu();
+        }
+        if ui.button(t!("sound.copy_trigger")).clicked() {
+            copy_trigger_snippet(ui, entry);
+            ui.close_menu();
+        }
+        if ui.button(t!("sound.mark_used")).clicked() {
+            gdsfx.usage_dialog = Some((entry.clone(), String::new()));
+            ui.close_menu();
+        }
+        ui.menu_button(t!("sound.add_to_collection"), |ui| {
+            for (index, collection) in get_collections().into_iter().enumerate() {
+                let contains = collection.entries.contains(&entry.id());
+                if ui.add_enabled(!contains, Button::new(&collection.name)).clicked() {
+                    add_to_collection(index, entry.id());
+                    ui.close_menu();
+                }
+            }
+            ui.separator();
+            ui.label(t!("sound.new_collection_hint"));
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut gdsfx.new_collection_name);
+                let name = gdsfx.new_collection_name.trim().to_string();
+                if ui.add_enabled(!name.is_empty(), Button::new(t!("common.create"))).clicked() {
+                    let index = add_collection(&name);
+                    add_to_collection(index, entry.id());
+                    gdsfx.new_collection_name.clear();
+                    ui.close_menu();
+                }
+            });
+        });
+        if entry.exists() {
+            if ui.button(t!("sound.delete")).clicked() {
+                entry.delete();
+                ui.close_menu();
+            }
+        } else if ui
+            .add_enabled(!is_offline() && !entry.is_custom(), Button::new(t!("sound.download")))
+            .clicked()
+        {
+            entry.download_and_store();
+            ui.close_menu();
+        }
+    });
+}
+
+fn favourite_sound(gdsfx: &mut GdSfx, entry: &LibraryEntry) {
+    let duplicates = gdsfx
+        .sfx_library
+        .as_ref()
+        .map(|library| library.duplicate_favourites(entry).into_iter().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    if duplicates.is_empty() {
+        add_favourite(entry.id());
+    } else {
+        gdsfx.pending_favourite = Some((entry.clone(), duplicates));
+    }
+}
+
+fn duplicate_favourite_dialog(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    let Some((entry, duplicates)) = gdsfx.pending_favourite.clone() else {
+        return;
+    };
+
+    let modal = Modal::new(ctx, "duplicate_favourite");
+    modal.open();
+    modal.show(|ui| {
+        modal.title(ui, t!("duplicate.title"));
+        modal.frame(ui, |ui| {
+            ui.label(t!("duplicate.body", name = entry.name(), id = entry.id()));
+            for duplicate in &duplicates {
+                ui.label(format!("{FAVOURITES_CHARACTER} {} (ID: {})", duplicate.name(), duplicate.id()));
+            }
+        });
+        modal.buttons(ui, |ui| {
+            if modal.button(ui, t!("duplicate.keep_existing")).clicked() {
+                gdsfx.pending_favourite = None;
+            }
+            if modal.button(ui, t!("duplicate.replace_existing")).clicked() {
+                for duplicate in &duplicates {
+                    remove_favourite(duplicate.id());
+                }
+                add_favourite(entry.id());
+                gdsfx.pending_favourite = None;
+            }
+            if modal.suggested_button(ui, t!("duplicate.keep_both")).clicked() {
+                add_favourite(entry.id());
+                gdsfx.pending_favourite = None;
+            }
+        });
+    });
+}
+
+fn another_instance_dialog(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    if !gdsfx.another_instance_running {
+        return;
+    }
+
+    let modal = Modal::new(ctx, "another_instance");
+    modal.open();
+    modal.show(|ui| {
+        modal.title(ui, t!("instance.title"));
+        modal.frame(ui, |ui| {
+            modal.body(ui, t!("instance.body"));
+        });
+        modal.buttons(ui, |ui| {
+            if modal.button(ui, t!("instance.exit")).clicked() {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            if modal.caution_button(ui, t!("instance.continue")).clicked() {
+                gdsfx.another_instance_running = false;
+            }
+        });
+    });
+}
+
+fn usage_dialog(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    let Some((entry, level)) = gdsfx.usage_dialog.as_mut() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut done = false;
+    egui::Window::new(t!("usage.title", name = entry.name()))
+        .id(egui::Id::new("usage_dialog"))
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            for used_level in get_levels(entry.id()) {
+                ui.horizontal(|ui| {
+                    if ui.small_button("❌").on_hover_text(t!("common.remove")).clicked() {
+                        unmark_used(entry.id(), &used_level);
+                    }
+                    ui.label(&used_level);
+                });
+            }
+            ui.horizontal(|ui| {
+                let response = ui.add(egui::TextEdit::singleline(level).hint_text(t!("usage.level_name")));
+                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let name = level.trim().to_string();
+                if (ui.add_enabled(!name.is_empty(), Button::new(t!("common.add"))).clicked() || submitted)
+                    && !name.is_empty()
+                {
+                    mark_used(entry.id(), &name);
+                    done = true;
+                }
+            });
+        });
+    if !open || done {
+        gdsfx.usage_dialog = None;
+    }
+}
+
+fn hover_preview(ui: &mut Ui, gdsfx: &mut GdSfx, entry: &LibraryEntry) {
+    // runs for every hovered button, so only the two fields are read
+    let (enabled, delay_ms) = {
+        let settings = settings::SETTINGS.lock().unwrap();
+        (settings.preview_on_hover, settings.hover_preview_delay_ms)
+    };
+    if !enabled {
+        return;
+    }
+
+    gdsfx.hovering_sound = true;
+
+    match gdsfx.hover_preview {
+        Some((id, _, _)) if id == entry.id() => {}
+        previous => {
+            if let Some((id, _, true)) = previous {
+                if now_playing() == Some(id) {
+                    stop_audio();
+                }
+            }
+            gdsfx.hover_preview = Some((entry.id(), Instant::now(), false));
+        }
+    }
+
+    let Some((_, start, played)) = gdsfx.hover_preview.as_mut() else {
+        return;
+    };
+    let delay = Duration::from_millis(delay_ms);
+    if !*played {
+        if start.elapsed() >= delay {
+            stop_audio();
+            play_sound_with_volume(entry, CDN_URL, HOVER_PREVIEW_VOLUME);
+            *played = true;
+        } else {
+            ui.ctx().request_repaint_after(delay - start.elapsed());
+        }
+    }
+}
+
+fn side_bar_sfx(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    let Some(sfx) = gdsfx.selected_sfx.clone() else {
+        return;
+    };
+    match get_settings().detail_panel_position {
+        DetailPanelPosition::Right => {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| sfx_details(ui, gdsfx, &sfx, false));
+            });
+        }
+        DetailPanelPosition::Bottom => {
+            egui::TopBottomPanel::bottom("detail_panel")
+                .resizable(true)
+                .default_height(TOTAL_HEIGHT / 3.0)
+                .show(ctx, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| sfx_details(ui, gdsfx, &sfx, true));
+                });
+        }
+        DetailPanelPosition::Floating => {
+            egui::Window::new(t!("details.title"))
+                .id(egui::Id::new("detail_window"))
+                .default_width(RIGHT_PANEL_WIDTH)
+                .resizable(true)
+                .show(ctx, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| sfx_details(ui, gdsfx, &sfx, false));
+                });
+        }
+    }
+}
+
+// `wide` lays the actions out in a row for short, wide layouts
+fn sfx_details(ui: &mut Ui, gdsfx: &mut GdSfx, sfx: &LibraryEntry, wide: bool) {
+    // ui.input(|input| {
+    // if input.modifiers.alt
+    // });
+    ui.heading(sfx.name());
+
+    ui.add_space(25.0);
+
+    ui.code(sfx.get_string());
+
+    ui.add_space(25.0);
+
+    ui.heading(t!("details.id", id = sfx.id()));
+    ui.heading(t!("details.category_id", id = sfx.parent()));
+    categories_breadcrumb(ui, gdsfx, sfx);
+    ui.heading(t!("details.size", size = convert(sfx.bytes() as f64)));
+    ui.heading(t!("details.duration", duration = stringify_duration(sfx.duration())));
+    // only the shown sound is worth analysing once the selection moves on
+    cancel_analyses_except(sfx.id());
+    match audio_info(sfx) {
+        Some(info) => {
+            ui.label(t!(
+                "details.audio_info",
+                format = info.format,
+                sample_rate = info.sample_rate,
+                channels = info.channels,
+                bit_depth = info.bit_depth,
+            ));
+        }
+        None if is_analysing(sfx.id()) => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.weak(t!("details.analysing_audio"));
+            });
+        }
+        None if has_audio_data(sfx) => {
+            ui.weak(t!("details.audio_info_unavailable"));
+        }
+        None => {
+            ui.weak(t!("details.download_for_audio_info"));
+        }
+    }
+
+    ui.add_space(10.0);
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label(t!("details.tags"));
+        for tag in get_tags(sfx.id()) {
+            if ui.small_button(format!("{tag} ❌")).on_hover_text(t!("details.remove_tag")).clicked() {
+                remove_tag(sfx.id(), &tag);
+            }
+        }
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut gdsfx.new_tag)
+                .hint_text(t!("details.add_tag"))
+                .desired_width(80.0),
+        );
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            let tag = gdsfx.new_tag.trim().to_string();
+            if !tag.is_empty() {
+                add_tag(sfx.id(), &tag);
+            }
+            gdsfx.new_tag.clear();
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(t!("details.rating"));
+        let rating = get_rating(sfx.id());
+        for star in 1..=MAX_RATING {
+            let filled = rating.is_some_and(|rating| star <= rating);
+            if ui.small_button(if filled { "★" } else { "☆" }).clicked() {
+                // clicking the current rating clears it
+                set_rating(sfx.id(), (rating != Some(star)).then_some(star));
+            }
+        }
+    });
+
+    if sfx.duration() > MIN_LOOP_LENGTH {
+        ui.add_space(10.0);
+        loop_region(ui, sfx);
+    }
+
+    if ARGS.debug {
+        ui.add_space(10.0);
+        debug_details(ui, sfx);
+    }
+
+    ui.add_space(50.0);
+
+    if wide {
+        ui.horizontal_wrapped(|ui| sfx_actions(ui, gdsfx, sfx));
+    } else {
+        sfx_actions(ui, gdsfx, sfx);
+    }
+}
+
+const LOOP_BAR_HEIGHT: f32 = 24.0;
+const LOOP_HANDLE_WIDTH: f32 = 3.0;
+
+// a start/end region of the sound that's previewed on repeat, stored per sound
+fn loop_region(ui: &mut Ui, sfx: &LibraryEntry) {
+    egui::CollapsingHeader::new(t!("loop.title"))
+        .id_source(("loop_region", sfx.id()))
+        .show(ui, |ui| {
+            let stored = get_loop_region(sfx.id());
+            let mut region = stored.unwrap_or((0, sfx.duration()));
+            if loop_region_bar(ui, sfx.duration(), &mut region) {
+                set_loop_region(sfx.id(), Some(region));
+            }
+            ui.label(t!(
+                "loop.region",
+                start = stringify_duration(region.0),
+                end = stringify_duration(region.1),
+            ));
+            ui.horizontal(|ui| {
+                if ui.button(t!("loop.preview")).clicked() {
+                    stop_audio();
+                    play_loop(sfx, CDN_URL, region);
+                }
+                if ui.button(t!("sound.stop")).clicked() {
+                    stop_audio();
+                }
+                if ui.add_enabled(stored.is_some(), Button::new(t!("loop.clear"))).clicked() {
+                    set_loop_region(sfx.id(), None);
+                }
+            });
+        });
+}
+
+// the whole sound as a bar, dragging moves whichever boundary is closer
+fn loop_region_bar(ui: &mut Ui, duration: i64, region: &mut (i64, i64)) -> bool {
+    let size = egui::vec2(ui.available_width(), LOOP_BAR_HEIGHT);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+    let to_x = |time: i64| rect.left() + rect.width() * time as f32 / duration as f32;
+    let to_time = |x: f32| (((x - rect.left()) / rect.width()).clamp(0.0, 1.0) * duration as f32).round() as i64;
+
+    let mut changed = false;
+    let dragging_start = response.id.with("dragging_start");
+    if response.drag_started() {
+        if let Some(pos) = response.interact_pointer_pos() {
+            let is_start = (pos.x - to_x(region.0)).abs() <= (pos.x - to_x(region.1)).abs();
+            ui.memory_mut(|mem| mem.data.insert_temp(dragging_start, is_start));
+        }
+    }
+    if response.dragged() {
+        let is_start = ui.memory(|mem| mem.data.get_temp::<bool>(dragging_start));
+        if let (Some(pos), Some(is_start)) = (response.interact_pointer_pos(), is_start) {
+            let time = to_time(pos.x);
+            if is_start {
+                region.0 = time.min(region.1 - MIN_LOOP_LENGTH).max(0);
+            } else {
+                region.1 = time.max(region.0 + MIN_LOOP_LENGTH).min(duration);
+            }
+            changed = true;
+        }
+    }
+
+    let visuals = ui.visuals();
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+    let selected = egui::Rect::from_x_y_ranges(to_x(region.0)..=to_x(region.1), rect.y_range());
+    painter.rect_filled(selected, 0.0, visuals.selection.bg_fill);
+    for time in [region.0, region.1] {
+        painter.vline(to_x(time), rect.y_range(), egui::Stroke::new(LOOP_HANDLE_WIDTH, visuals.strong_text_color()));
+    }
+    if response.hovered() || response.dragged() {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+    }
+    changed
+}
+
+const DEBUG_HEAD_BYTES: usize = 256;
+
+// only shown with --debug, for looking into decode failures and library format changes
+fn debug_details(ui: &mut Ui, sfx: &LibraryEntry) {
+    egui::CollapsingHeader::new(t!("details.debug"))
+        .id_source(("debug_details", sfx.id()))
+        .show(ui, |ui| {
+            ui.label(t!("details.debug_path", path = sfx.path().display()));
+            ui.label(t!("details.debug_metadata"));
+            ui.code(format!("{sfx:#?}"));
+            match audio_head(sfx, DEBUG_HEAD_BYTES) {
+                Some(head) => {
+                    ui.label(t!("details.debug_head", count = head.len()));
+                    egui::ScrollArea::horizontal()
+                        .id_source(("debug_hex", sfx.id()))
+                        .show(ui, |ui| ui.code(hex_dump(&head)));
+                }
+                None => {
+                    ui.weak(t!("details.download_for_audio_info"));
+                }
+            }
+        });
+}
+
+// the categories leading to the sound, clicking one shows only the sounds in it
+fn categories_breadcrumb(ui: &mut Ui, gdsfx: &mut GdSfx, sfx: &LibraryEntry) {
+    let Some(library) = gdsfx.sfx_library.as_ref() else {
+        return;
+    };
+    let categories = library
+        .path_to(sfx.id())
+        .into_iter()
+        .filter(|entry| entry.is_category() && entry.parent() != 0) // skip the root
+        .map(|entry| (entry.id(), entry.name().to_string()))
+        .collect::<Vec<_>>();
+
+    ui.horizontal_wrapped(|ui| {
+        for (i, (id, name)) in categories.into_iter().enumerate() {
+            if i > 0 {
+                ui.label("›");
+            }
+            if ui.link(name).on_hover_text(t!("details.category_filter")).clicked() {
+                gdsfx.filters.categories = vec![id];
+                gdsfx.stage = Stage::Library;
+            }
+        }
+    });
+}
+
+fn sfx_actions(ui: &mut Ui, gdsfx: &mut GdSfx, sfx: &LibraryEntry) {
+    if ui
+        .add_enabled(!sfx.exists() && !is_offline() && !sfx.is_custom(), Button::new(t!("sound.download")))
+        .clicked()
+    {
+        sfx.download_and_store();
+    }
+    if ui
+        .add_enabled(sfx.exists(), Button::new(t!("sound.delete")))
+        .clicked()
+    {
+        sfx.delete();
+    }
+    if ui.button(t!("sound.play")).clicked() {
+        play_sound(sfx, CDN_URL);
+    }
+    if ui.button(t!("sound.stop")).clicked() {
+        stop_audio();
+    }
+    if ui.button(t!("sound.copy_trigger")).clicked() {
+        copy_trigger_snippet(ui, sfx);
+    }
+    if ui.button(t!("sound.find_similar")).clicked() {
+        if let Some(library) = gdsfx.sfx_library.as_ref() {
+            let similar = library.similar_sounds(sfx).into_iter().cloned().collect();
+            gdsfx.similar_sounds = Some((sfx.clone(), similar));
+        }
+    }
+}
+
+fn similar_sounds_window(ctx: &egui::Context, gdsfx: &mut GdSfx) {
+    let Some((sound, similar)) = gdsfx.similar_sounds.clone() else {
+        return;
+    };
+
+    let mut open = true;
+    egui::Window::new(t!("similar.title", name = sound.name()))
+        .id(egui::Id::new("similar_sounds"))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            if similar.is_empty() {
+                ui.label(t!("similar.empty"));
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for entry in &similar {
+                    sfx_button(ui, gdsfx, entry);
+                }
+            });
+        });
+    if !open {
+        gdsfx.similar_sounds = None;
+    }
+}
+
+fn notifications_overlay(ctx: &egui::Context) {
+    let notifications = active_notifications();
+    if notifications.is_empty() {
+        return;
+    }
+
+    egui::Area::new("notifications")
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+        .show(ctx, |ui| {
+            for notification in notifications {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    if notification.is_error {
+                        ui.colored_label(ui.visuals().error_fg_color, &notification.message);
+                    } else {
+                        ui.label(&notification.message);
+                    }
+                });
+            }
+        });
+
+    // keep repainting so expired notifications disappear
+    ctx.request_repaint_after(std::time::Duration::from_millis(250));
+}
+
+// adds a virtual category with the recently added sounds that passed the filter.
+// only the drawn tree gets it, bulk actions and selections go by the search results and ids
+fn insert_recently_added(root: &mut LibraryEntry, recently_added: &HashSet<i64>) {
+    if recently_added.is_empty() {
+        return;
+    }
+    let sounds = root
+        .sounds()
+        .into_iter()
+        .filter(|sound| recently_added.contains(&sound.id()))
+        .cloned()
+        .collect::<Vec<_>>();
+    if sounds.is_empty() {
+        return;
+    }
+    let root_id = root.id();
+    if let LibraryEntry::Category(CategoryEntry { children, .. }) = root {
+        children.insert(
+            0,
+            LibraryEntry::Category(CategoryEntry {
+                id: RECENTLY_ADDED_CATEGORY_ID,
+                name: RECENTLY_ADDED_CATEGORY_NAME.to_string(),
+                parent: root_id,
+                children: sounds,
+                original_index: 0, // shown first, like it's inserted
+            }),
+        );
+    }
+}
+
+// drops every category without a single sound anywhere below it, the node itself is kept
+fn remove_empty_category_nodes(node: &mut LibraryEntry) {
+    if let LibraryEntry::Category(CategoryEntry { children, .. }) = node {
+        for child in children.iter_mut() {
+            remove_empty_category_nodes(child);
+        }
+        // bottom up, so a category with only empty subcategories has no children left by now
+        children.retain(|child| child.is_sound() || child.children().is_some_and(|c| !c.is_empty()));
+    }
+}
+
+// closest sound names to a query that matched nothing
+fn did_you_mean(root: &LibraryEntry, query: &str) -> Vec<String> {
+    let mut names = root
+        .sounds()
+        .into_iter()
+        .map(|sound| sound.name().to_string())
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    names.sort_by_cached_key(|name| strsim::levenshtein(query, &fold_case(name)));
+    names.truncate(SEARCH_SUGGESTIONS);
+    names
+}
+
+// with a minimum similarity the names are fuzzy matched and their scores recorded,
+// names are looked up in the index rather than folded again
+fn filter_sounds(tree: &LibraryEntry, names: &NameIndex, key: &SearchKey, scores: &mut SearchScores) -> Vec<LibraryEntry> {
+    match tree {
+        LibraryEntry::Sound(SoundEntry { id, .. }) => {
+            let name_match = match key.min_similarity {
+                Some(min) => {
+                    let score = names.similarity(tree, &key.query, key.transliterate);
+                    if score >= min {
+                        scores.insert(*id, score);
+                    }
+                    score >= min
+                }
+                None => names.matches(tree, &key.query, key.transliterate),
+            };
+            if name_match && key.filters.matches(tree) {
+                vec![tree.clone()] // Keep the sound if it contains the filter string
+            } else {
+                vec![] // Filter out the sound if it doesn't contain the filter string
+            }
+        }
+        LibraryEntry::Category(CategoryEntry {
+            id,
+            name,
+            parent,
+            children,
+            original_index,
+        }) => {
+            if !key.filters.show_hidden && is_hidden(*id) {
+                return vec![];
+            }
+            // sounds in subcategories of a filtered category count as well
+            let key = if key.filters.categories.contains(id) {
+                &SearchKey {
+                    filters: Filters { categories: vec![], ..key.filters.clone() },
+                    ..key.clone()
+                }
+            } else {
+                key
+            };
+            // the whole subtree of a matching category is kept, the other filters still apply
+            let name_match = key.match_categories
+                && !key.query.is_empty()
+                && *parent != 0 // the root is named after the library version
+                && match key.min_similarity {
+                    Some(min) => name_similarity(name, &key.query) >= min,
+                    None => name_matches(name, &key.query),
+                };
+            let key = if name_match {
+                &SearchKey {
+                    query: String::new(),
+                    min_similarity: None,
+                    ..key.clone()
+                }
+            } else {
+                key
+            };
             // Recursively filter sounds in subcategories
             let filtered_sounds: Vec<LibraryEntry> = children
                 .iter()
-                .flat_map(|node| filter_sounds(node, filter_str))
+                .flat_map(|node| filter_sounds(node, names, key, scores))
                 .collect();
 
             // Only keep the category if it contains any filtered sounds
             if !filtered_sounds.is_empty() {
-                vec![LibraryEntry::Category {
+                // a category ranks by its best kept sound, so sorting only has to look it up
+                let best = filtered_sounds
+                    .iter()
+                    .filter_map(|child| scores.get(&child.id()).copied())
+                    .reduce(f64::max);
+                if let Some(best) = best {
+                    scores.insert(*id, best);
+                }
+                vec![LibraryEntry::Category(CategoryEntry {
                     name: name.clone(),
                     parent: *parent,
                     id: *id,
                     children: filtered_sounds,
-                }]
+                    original_index: *original_index,
+                })]
             } else {
                 vec![] // Filter out the category if it doesn't contain any filtered sounds
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::library::{CategoryEntry, LibraryEntry, SoundEntry};
+
+    use super::remove_empty_category_nodes;
+
+    fn category(id: i64, parent: i64, children: Vec<LibraryEntry>) -> LibraryEntry {
+        LibraryEntry::Category(CategoryEntry {
+            id,
+            name: format!("category {id}"),
+            parent,
+            children,
+            original_index: 0,
+        })
+    }
+
+    fn sound(id: i64, parent: i64) -> LibraryEntry {
+        LibraryEntry::Sound(SoundEntry {
+            id,
+            name: format!("sound {id}"),
+            parent,
+            bytes: 0,
+            duration: 0,
+            original_index: 0,
+        })
+    }
+
+    fn child_ids(node: &LibraryEntry) -> Vec<i64> {
+        node.children().unwrap().iter().map(LibraryEntry::id).collect()
+    }
+
+    #[test]
+    fn removes_category_with_only_empty_subcategories() {
+        let mut root = category(1, 0, vec![
+            category(2, 1, vec![category(3, 2, vec![]), category(4, 2, vec![category(5, 4, vec![])])]),
+            category(6, 1, vec![sound(7, 6)]),
+        ]);
+        remove_empty_category_nodes(&mut root);
+        assert_eq!(child_ids(&root), vec![6]);
+    }
+
+    #[test]
+    fn keeps_deep_chain_ending_in_a_sound() {
+        let mut root = category(1, 0, vec![
+            category(2, 1, vec![category(3, 2, vec![category(4, 3, vec![sound(5, 4)])])]),
+        ]);
+        remove_empty_category_nodes(&mut root);
+        let chain = &root.children().unwrap()[0];
+        assert_eq!(chain.id(), 2);
+        let next = &chain.children().unwrap()[0];
+        assert_eq!(next.id(), 3);
+        assert_eq!(child_ids(&next.children().unwrap()[0]), vec![5]);
+    }
+
+    #[test]
+    fn removes_empty_top_level_category() {
+        // these used to be kept because their parent is the root
+        let mut root = category(1, 0, vec![category(2, 1, vec![]), category(3, 1, vec![sound(4, 3)])]);
+        remove_empty_category_nodes(&mut root);
+        assert_eq!(child_ids(&root), vec![3]);
+    }
+
+    #[test]
+    fn never_removes_the_root() {
+        let mut root = category(1, 0, vec![category(2, 1, vec![])]);
+        remove_empty_category_nodes(&mut root);
+        assert_eq!(root.id(), 1);
+        assert!(child_ids(&root).is_empty());
+    }
+}
@@ -0,0 +1,62 @@
+use std::{path::PathBuf, sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex}, fs};
+
+use eframe::epaint::ahash::HashSet;
+use lazy_static::lazy_static;
+
+use crate::{autosave::Autosave, util::GD_FOLDER, encoding::{full_decode, full_encode}};
+
+lazy_static!{
+    pub static ref HIDDEN_FILE: PathBuf = GD_FOLDER.join("gdsfx_hidden.dat");
+    // ids of hidden sounds and categories
+    pub static ref HIDDEN: Arc<Mutex<HashSet<i64>>> = Arc::new(Mutex::new(read_file()));
+}
+
+pub static AUTOSAVE: Autosave = Autosave::new(save);
+
+// bumped on every change so searches know to run again
+static HIDDEN_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+pub fn read_file() -> HashSet<i64> {
+    if HIDDEN_FILE.exists() {
+        let data = fs::read(HIDDEN_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        HashSet::default()
+    }
+}
+
+pub fn save() {
+    let string = serde_json::to_string(&*HIDDEN.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(HIDDEN_FILE.as_path(), data).unwrap();
+}
+
+fn hidden_changed() {
+    HIDDEN_GENERATION.fetch_add(1, Ordering::Relaxed);
+    AUTOSAVE.mark_dirty();
+}
+
+pub fn hidden_generation() -> usize {
+    HIDDEN_GENERATION.load(Ordering::Relaxed)
+}
+
+pub fn is_hidden(id: i64) -> bool {
+    HIDDEN.lock().unwrap().contains(&id)
+}
+
+pub fn get_hidden() -> Vec<i64> {
+    let mut hidden = HIDDEN.lock().unwrap().iter().copied().collect::<Vec<_>>();
+    hidden.sort_unstable();
+    hidden
+}
+
+pub fn hide(id: i64) {
+    HIDDEN.lock().unwrap().insert(id);
+    hidden_changed();
+}
+
+pub fn unhide(id: i64) {
+    HIDDEN.lock().unwrap().remove(&id);
+    hidden_changed();
+}
@@ -0,0 +1,41 @@
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers};
+
+use crate::locale::t;
+
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    pub reload_library: KeyboardShortcut,
+    pub show_help: KeyboardShortcut,
+    pub toggle_fullscreen: KeyboardShortcut,
+    pub go_to_id: KeyboardShortcut,
+    pub undo_tags: KeyboardShortcut,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            reload_library: KeyboardShortcut::new(Modifiers::NONE, Key::F5),
+            show_help: KeyboardShortcut::new(Modifiers::NONE, Key::F1),
+            toggle_fullscreen: KeyboardShortcut::new(Modifiers::NONE, Key::F11),
+            go_to_id: KeyboardShortcut::new(Modifiers::COMMAND, Key::G),
+            undo_tags: KeyboardShortcut::new(Modifiers::COMMAND, Key::Z),
+        }
+    }
+}
+
+impl KeyBindings {
+    // (action, shortcut) pairs, used for the help dialog
+    pub fn list(&self) -> Vec<(&'static str, &KeyboardShortcut)> {
+        vec![
+            (t!("help.reload_library"), &self.reload_library),
+            (t!("help.show_help"), &self.show_help),
+            (t!("help.toggle_fullscreen"), &self.toggle_fullscreen),
+            (t!("help.go_to_id"), &self.go_to_id),
+            (t!("help.undo_tags"), &self.undo_tags),
+        ]
+    }
+
+    pub fn format(ctx: &egui::Context, shortcut: &KeyboardShortcut) -> String {
+        ctx.format_shortcut(shortcut)
+    }
+}
@@ -1,58 +1,182 @@
-use std::{fs, path::PathBuf};
+use std::{fs, path::PathBuf, sync::{atomic::Ordering, Arc}};
 
-use eframe::epaint::ahash::{HashMap, HashMapExt};
+use eframe::epaint::ahash::{HashMap, HashMapExt, HashSet};
 use slab_tree::{NodeId, NodeRef, TreeBuilder};
 
 use crate::{
-    encoding::full_decode,
-    favourites::{has_favourite, FAVOURITES_CHARACTER},
+    encoding::try_full_decode,
+    failed_downloads::remove_failed_download,
+    favourites::{add_favourite, has_favourite, remove_favourite, FAVOURITES_CHARACTER},
+    filters::NameIndex,
+    locale::t,
+    notifications::log_error,
     requests::{download_sfx, CDN_URL},
-    stats::{add_file_to_stats, remove_file_from_stats},
-    util::{GD_FOLDER, LOCAL_SFX_LIBRARY},
+    settings::get_settings,
+    stats::{add_file_to_stats, remove_file_from_stats, EXISTING_SOUND_FILES, SOUND_FILES_LOCK, SOUND_FILES_SCANNED},
+    util::{GD_FOLDER, LOCAL_SFX_LIBRARY, PARTIAL_DOWNLOAD_EXTENSION, SHUTTING_DOWN},
 };
 
 #[derive(Debug, Clone)]
 pub struct Library {
     pub sound_effects: LibraryEntry,
     pub credits: Vec<Credit>,
+    // id -> child indices leading from the root to the entry
+    pub index: HashMap<i64, Vec<usize>>,
+    // shared with the search thread instead of being cloned for every search
+    pub names: Arc<NameIndex>,
+    // malformed entries left out while parsing
+    pub skipped_entries: usize,
+    pub sound_count: usize, // counted with the index, not every frame
+    pub warnings: Vec<ValidationError>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    // the entry is left out, there's nowhere to put it
+    UnknownParent { id: i64, parent: i64 },
+    // only the first entry with the id is kept
+    DuplicateId(i64),
+    EmptySound(i64),
+    NegativeDuration(i64),
+}
+
+impl ValidationError {
+    pub fn message(&self) -> String {
+        match self {
+            ValidationError::UnknownParent { id, parent } => t!("library.warning.unknown_parent", id = id, parent = parent),
+            ValidationError::DuplicateId(id) => t!("library.warning.duplicate_id", id = id),
+            ValidationError::EmptySound(id) => t!("library.warning.empty_sound", id = id),
+            ValidationError::NegativeDuration(id) => t!("library.warning.negative_duration", id = id),
+        }
+    }
+}
+
+// the first entry is the root, every other one needs a category before it as its parent
+fn validate_entries(entries: Vec<LibraryEntry>) -> (Vec<LibraryEntry>, Vec<ValidationError>) {
+    let mut warnings = Vec::new();
+    let mut ids = HashSet::default();
+    let mut categories = HashSet::default();
+    let mut valid = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let id = entry.id();
+        if !ids.insert(id) {
+            warnings.push(ValidationError::DuplicateId(id));
+            continue;
+        }
+        if !valid.is_empty() && !categories.contains(&entry.parent()) {
+            warnings.push(ValidationError::UnknownParent { id, parent: entry.parent() });
+            continue;
+        }
+        if let LibraryEntry::Sound(sound) = &entry {
+            if sound.bytes <= 0 {
+                warnings.push(ValidationError::EmptySound(id));
+            }
+            if sound.duration < 0 {
+                warnings.push(ValidationError::NegativeDuration(id));
+            }
+        } else {
+            categories.insert(id);
+        }
+        valid.push(entry);
+    }
+    (valid, warnings)
 }
 
 #[derive(Debug, Clone)]
 pub enum LibraryEntry {
-    Category {
-        // 3544,Aquatic Sounds,1,1,0,0;
-        id: i64,
-        name: String,
-        parent: i64,
-        children: Vec<LibraryEntry>,
-    },
-    Sound {
-        // 10728,Background Ambience Loop 01,0,10642,96677,699;
-        id: i64,
-        name: String,
-        parent: i64,
-        bytes: i64,
-        duration: i64, // in centiseconds
-    },
+    Category(CategoryEntry),
+    Sound(SoundEntry),
+}
+
+#[derive(Debug, Clone)]
+pub struct CategoryEntry {
+    // 3544,Aquatic Sounds,1,1,0,0;
+    pub id: i64,
+    pub name: String,
+    pub parent: i64,
+    pub children: Vec<LibraryEntry>,
+    pub original_index: usize, // position in the library file, for the default sorting
 }
 
+#[derive(Debug, Clone)]
+pub struct SoundEntry {
+    // 10728,Background Ambience Loop 01,0,10642,96677,699;
+    pub id: i64,
+    pub name: String,
+    pub parent: i64,
+    pub bytes: i64,
+    pub duration: i64, // in centiseconds
+    pub original_index: usize,
+}
+
+// custom sounds and other entries that aren't in the library file go after everything else
+pub const NO_ORIGINAL_INDEX: usize = usize::MAX;
+
+pub const CUSTOM_CATEGORY_ID: i64 = -1;
+pub const CUSTOM_CATEGORY_NAME: &str = "Custom";
+pub const RECENTLY_ADDED_CATEGORY_ID: i64 = i64::MIN;
+pub const RECENTLY_ADDED_CATEGORY_NAME: &str = "Recently Added";
+
 #[derive(Debug, Clone)]
 pub struct Credit {
     pub name: String,
     pub link: String,
 }
 
+impl From<CategoryEntry> for LibraryEntry {
+    fn from(category: CategoryEntry) -> Self {
+        LibraryEntry::Category(category)
+    }
+}
+
+impl From<SoundEntry> for LibraryEntry {
+    fn from(sound: SoundEntry) -> Self {
+        LibraryEntry::Sound(sound)
+    }
+}
+
+// converting to one kind hands back the other as the error, so both cases are always handled
+impl<'a> TryFrom<&'a LibraryEntry> for &'a SoundEntry {
+    type Error = &'a CategoryEntry;
+
+    fn try_from(entry: &'a LibraryEntry) -> Result<Self, Self::Error> {
+        match entry {
+            LibraryEntry::Sound(sound) => Ok(sound),
+            LibraryEntry::Category(category) => Err(category),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a LibraryEntry> for &'a CategoryEntry {
+    type Error = &'a SoundEntry;
+
+    fn try_from(entry: &'a LibraryEntry) -> Result<Self, Self::Error> {
+        match entry {
+            LibraryEntry::Category(category) => Ok(category),
+            LibraryEntry::Sound(sound) => Err(sound),
+        }
+    }
+}
+
+// `Aquatic Sounds (category 3544)`, for logs and messages
+impl std::fmt::Display for LibraryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.is_category() { "category" } else { "sound" };
+        write!(f, "{} ({kind} {})", self.name(), self.id())
+    }
+}
+
 impl LibraryEntry {
     pub fn id(&self) -> i64 {
         match self {
-            LibraryEntry::Category { id, .. } => *id,
-            LibraryEntry::Sound { id, .. } => *id,
+            LibraryEntry::Category(category) => category.id,
+            LibraryEntry::Sound(sound) => sound.id,
         }
     }
     pub fn name(&self) -> &str {
         match self {
-            LibraryEntry::Category { name, .. } => name,
-            LibraryEntry::Sound { name, .. } => name,
+            LibraryEntry::Category(category) => &category.name,
+            LibraryEntry::Sound(sound) => &sound.name,
         }
     }
     pub fn pretty_name(&self) -> String {
@@ -64,48 +188,103 @@ impl LibraryEntry {
     }
     pub fn is_category(&self) -> bool {
         match self {
-            LibraryEntry::Category { .. } => true,
-            LibraryEntry::Sound { .. } => false,
+            LibraryEntry::Category(_) => true,
+            LibraryEntry::Sound(_) => false,
         }
     }
-    #[allow(unused)]
     pub fn is_sound(&self) -> bool {
         match self {
-            LibraryEntry::Category { .. } => false,
-            LibraryEntry::Sound { .. } => true,
+            LibraryEntry::Category(_) => false,
+            LibraryEntry::Sound(_) => true,
         }
     }
     pub fn parent(&self) -> i64 {
         match self {
-            LibraryEntry::Category { parent, .. } => *parent,
-            LibraryEntry::Sound { parent, .. } => *parent,
+            LibraryEntry::Category(category) => category.parent,
+            LibraryEntry::Sound(sound) => sound.parent,
         }
     }
     pub fn bytes(&self) -> i64 {
         match self {
-            LibraryEntry::Sound { bytes, .. } => *bytes,
-            LibraryEntry::Category { .. } => 0,
+            LibraryEntry::Sound(sound) => sound.bytes,
+            LibraryEntry::Category(_) => 0,
         }
     }
     pub fn duration(&self) -> i64 {
         match self {
-            LibraryEntry::Sound { duration, .. } => *duration,
-            LibraryEntry::Category { .. } => 0,
+            LibraryEntry::Sound(sound) => sound.duration,
+            LibraryEntry::Category(_) => 0,
+        }
+    }
+    pub fn original_index(&self) -> usize {
+        match self {
+            LibraryEntry::Category(category) => category.original_index,
+            LibraryEntry::Sound(sound) => sound.original_index,
         }
     }
     pub fn push_entry(&mut self, entry: LibraryEntry) {
-        if let LibraryEntry::Category { children, .. } = self {
-            children.push(entry);
+        if let LibraryEntry::Category(category) = self {
+            category.children.push(entry);
         }
     }
-    #[allow(unused)]
     pub fn children(&self) -> Option<&Vec<LibraryEntry>> {
-        if let LibraryEntry::Category { children, .. } = self {
-            Some(children)
+        if let LibraryEntry::Category(category) = self {
+            Some(&category.children)
         } else {
             None
         }
     }
+    pub fn find(&self, id: i64) -> Option<&LibraryEntry> {
+        if self.id() == id {
+            return Some(self);
+        }
+        self.children()?.iter().find_map(|child| child.find(id))
+    }
+    pub fn sounds(&self) -> Vec<&LibraryEntry> {
+        match self {
+            LibraryEntry::Category(category) => {
+                category.children.iter().flat_map(|child| child.sounds()).collect()
+            }
+            LibraryEntry::Sound(_) => vec![self],
+        }
+    }
+    // every sound below this entry with the names of the categories leading to it,
+    // not counting this entry itself, in tree order
+    pub fn iter_with_path(&self) -> impl Iterator<Item = (Vec<String>, &LibraryEntry)> {
+        // (entry, how many of its ancestors are in the path), reversed so they come off the stack in order
+        let mut stack = match self {
+            LibraryEntry::Category(category) => category.children.iter().rev().map(|child| (child, 0)).collect(),
+            LibraryEntry::Sound(_) => vec![(self, 0)],
+        };
+        let mut path = Vec::new();
+        std::iter::from_fn(move || {
+            while let Some((entry, depth)) = stack.pop() {
+                path.truncate(depth);
+                match entry {
+                    LibraryEntry::Category(category) => {
+                        path.push(category.name.clone());
+                        stack.extend(category.children.iter().rev().map(|child| (child, path.len())));
+                    }
+                    LibraryEntry::Sound(_) => return Some((path.clone(), entry)),
+                }
+            }
+            None
+        })
+    }
+    // same metadata, and same content if both files are downloaded
+    pub fn is_duplicate_of(&self, other: &LibraryEntry) -> bool {
+        if self.is_category() || other.is_category() || self.id() == other.id() {
+            return false;
+        }
+        if self.bytes() != other.bytes() || self.duration() != other.duration() {
+            return false;
+        }
+        if self.exists() && other.exists() {
+            fs::read(self.path()).ok() == fs::read(other.path()).ok()
+        } else {
+            true
+        }
+    }
     pub fn get_string(&self) -> String {
         format!(
             "{},{},{},{},{},{}",
@@ -117,34 +296,63 @@ impl LibraryEntry {
             self.duration(),
         )
     }
-    pub fn parse_string(string: &str) -> Self {
-        let mut entries: Vec<LibraryEntry> = string
-            .split(';')
-            .filter_map(|line| {
-                let segments = line.split(',').collect::<Vec<&str>>();
+    fn parse_line(line: &str) -> Option<Self> {
+        let segments = line.split(',').collect::<Vec<&str>>();
 
-                if segments.len() != 6 {
-                    return None;
-                }
+        if segments.len() != 6 {
+            return None;
+        }
 
-                match segments[2] {
-                    "0" => Some(LibraryEntry::Sound {
-                        id: segments[0].parse().unwrap(),
-                        name: segments[1].to_string(),
-                        parent: segments[3].parse().unwrap(),
-                        bytes: segments[4].parse().unwrap(),
-                        duration: segments[5].parse().unwrap(),
-                    }),
-                    "1" => Some(LibraryEntry::Category {
-                        id: segments[0].parse().unwrap(),
-                        name: segments[1].to_string(),
-                        parent: segments[3].parse().unwrap(),
-                        children: vec![],
-                    }),
-                    _ => None,
+        let number = |index: usize| segments[index].parse::<i64>().ok();
+
+        match segments[2] {
+            "0" => Some(
+                SoundEntry {
+                    id: number(0)?,
+                    name: segments[1].to_string(),
+                    parent: number(3)?,
+                    bytes: number(4)?,
+                    duration: number(5)?,
+                    original_index: NO_ORIGINAL_INDEX,
+                }
+                .into(),
+            ),
+            "1" => Some(
+                CategoryEntry {
+                    id: number(0)?,
+                    name: segments[1].to_string(),
+                    parent: number(3)?,
+                    children: vec![],
+                    original_index: NO_ORIGINAL_INDEX,
                 }
+                .into(),
+            ),
+            _ => None,
+        }
+    }
+    // returns the root entry, how many malformed entries were skipped and what looked wrong in the rest
+    pub fn parse_string(string: &str) -> (Self, usize, Vec<ValidationError>) {
+        let mut skipped = 0;
+        let entries: Vec<LibraryEntry> = string
+            .split(';')
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| {
+                let entry = LibraryEntry::parse_line(line);
+                if entry.is_none() {
+                    // the count is shown when the library loads, the lines only go to the error log
+                    log_error(t!("error.skipped_entry", line = line.trim()));
+                    skipped += 1;
+                }
+                entry
             })
             .collect::<Vec<_>>();
+        let (mut entries, warnings) = validate_entries(entries);
+        for (index, entry) in entries.iter_mut().enumerate() {
+            match entry {
+                LibraryEntry::Category(category) => category.original_index = index,
+                LibraryEntry::Sound(sound) => sound.original_index = index,
+            }
+        }
 
         let mut library_map: HashMap<i64, (&mut LibraryEntry, NodeId)> =
             HashMap::with_capacity(entries.len());
@@ -182,7 +390,7 @@ impl LibraryEntry {
 
         let root = library_map.get(&root_id).unwrap();
 
-        root.0.clone()
+        (root.0.clone(), skipped, warnings)
     }
     pub fn filename(&self) -> String {
         format!("s{}.ogg", self.id())
@@ -204,10 +412,8 @@ impl LibraryEntry {
             data.clone()
         } else if path.exists() {
             fs::read(path).unwrap()
-        } else if let Some(data) = download_sfx(cdn_url, self) {
-            data
         } else {
-            return None;
+            download_sfx(cdn_url, self)?
         };
 
         if cache_data {
@@ -216,22 +422,87 @@ impl LibraryEntry {
 
         Some(data)
     }
+    pub fn partial_path(&self) -> PathBuf {
+        GD_FOLDER.join(format!("{}.{PARTIAL_DOWNLOAD_EXTENSION}", self.filename()))
+    }
+    // only a sound that wasn't downloaded before is auto favourited,
+    // so unfavouriting a downloaded sound sticks
     pub fn download_and_store(&self) {
-        if let Some(content) = self.download(CDN_URL) {
-            fs::write(self.path(), content).unwrap();
-            add_file_to_stats(self.id());
+        let is_new = !self.exists();
+        if self.store_file() && is_new && get_settings().favourite_on_download {
+            add_favourite(self.id());
+        }
+    }
+    // replaces the file without touching the favourites, e.g. when it might be corrupted
+    // the old file stays until the new one is on disk, a failed fetch keeps it
+    pub fn redownload(&self) -> bool {
+        if self.is_custom() {
+            return false;
+        }
+        // straight from the cdn, download() would just read the stale file back
+        let Some(content) = download_sfx(CDN_URL, self) else {
+            return false;
+        };
+        let written = self.write_file(&content);
+        if written {
+            LOCAL_SFX_LIBRARY.lock().insert(self.id(), content);
         }
+        written
     }
     pub fn delete(&self) {
-        let _ = fs::remove_file(self.path());
+        if self.delete_file() && get_settings().unfavourite_on_delete {
+            remove_favourite(self.id());
+        }
+    }
+    // true if the file was written
+    fn store_file(&self) -> bool {
+        if self.is_custom() {
+            return false;
+        }
+        let Some(content) = self.download(CDN_URL) else {
+            return false;
+        };
+        self.write_file(&content)
+    }
+    // the rename replaces an existing file in one step
+    fn write_file(&self, content: &[u8]) -> bool {
+        if SHUTTING_DOWN.load(Ordering::Relaxed) {
+            return false;
+        }
+        // write to a separate file first so an interrupted write never leaves a broken sound
+        let _lock = SOUND_FILES_LOCK.lock().unwrap();
+        let partial_path = self.partial_path();
+        fs::write(&partial_path, content).unwrap();
+        fs::rename(&partial_path, self.path()).unwrap();
+        add_file_to_stats(self.id());
+        remove_failed_download(self.id());
+        true
+    }
+    // true if there was a file to delete
+    fn delete_file(&self) -> bool {
+        if self.is_custom() {
+            return false;
+        }
+        let _lock = SOUND_FILES_LOCK.lock().unwrap();
+        let existed = fs::remove_file(self.path()).is_ok();
         remove_file_from_stats(self.id());
+        existed
     }
+    // tracked in memory so the ui doesn't have to hit the disk for every sound,
+    // the disk is only asked while the startup scan is still running
     pub fn exists(&self) -> bool {
-        self.path().exists()
+        if !SOUND_FILES_SCANNED.load(Ordering::Relaxed) {
+            return self.path().exists();
+        }
+        EXISTING_SOUND_FILES.lock().unwrap().contains(&self.id())
     }
     pub fn is_favourite(&self) -> bool {
         has_favourite(self.id())
     }
+    // imported sounds use synthetic negative ids and only live in memory
+    pub fn is_custom(&self) -> bool {
+        self.id() < 0
+    }
 }
 
 impl Credit {
@@ -257,15 +528,173 @@ impl Library {
     pub fn parse_string(string: &str) -> Self {
         let (sound_effects, credits) = string.split_once('|').unwrap_or((string, ""));
 
-        Library {
-            sound_effects: LibraryEntry::parse_string(sound_effects),
+        let (sound_effects, skipped_entries, warnings) = LibraryEntry::parse_string(sound_effects);
+        let mut library = Library {
+            sound_effects,
             credits: Credit::parse_string(credits),
+            index: HashMap::new(),
+            names: Default::default(),
+            skipped_entries,
+            warnings,
+            sound_count: 0,
+        };
+        library.build_index();
+        library
+    }
+    pub fn build_index(&mut self) {
+        fn recurse(entry: &LibraryEntry, path: &mut Vec<usize>, index: &mut HashMap<i64, Vec<usize>>) {
+            index.insert(entry.id(), path.clone());
+            if let LibraryEntry::Category(category) = entry {
+                for (i, child) in category.children.iter().enumerate() {
+                    path.push(i);
+                    recurse(child, path, index);
+                    path.pop();
+                }
+            }
         }
+        self.index.clear();
+        recurse(&self.sound_effects, &mut vec![], &mut self.index);
+        self.sound_count = self.sound_effects.sounds().len();
+        self.names = Arc::new(NameIndex::build(&self.sound_effects));
     }
+    pub fn add_custom_sound(&mut self, sound: LibraryEntry) {
+        let LibraryEntry::Category(CategoryEntry { id: root_id, children, .. }) = &mut self.sound_effects else {
+            return;
+        };
+        let index = match children.iter().position(|c| c.id() == CUSTOM_CATEGORY_ID) {
+            Some(index) => index,
+            None => {
+                children.push(
+                    CategoryEntry {
+                        id: CUSTOM_CATEGORY_ID,
+                        name: CUSTOM_CATEGORY_NAME.to_string(),
+                        parent: *root_id,
+                        children: vec![],
+                        original_index: NO_ORIGINAL_INDEX,
+                    }
+                    .into(),
+                );
+                children.len() - 1
+            }
+        };
+        children[index].push_entry(sound);
+        self.build_index();
+    }
+    pub fn duplicate_favourites(&self, sound: &LibraryEntry) -> Vec<&LibraryEntry> {
+        self.sound_effects
+            .sounds()
+            .into_iter()
+            .filter(|other| other.is_favourite() && sound.is_duplicate_of(other))
+            .collect()
+    }
+    // entries from the root down to (and including) the entry with this id
+    pub fn path_to(&self, id: i64) -> Vec<&LibraryEntry> {
+        let Some(path) = self.index.get(&id) else {
+            return vec![];
+        };
+        let mut entry = &self.sound_effects;
+        let mut entries = vec![entry];
+        for &i in path {
+            match entry.children().and_then(|children| children.get(i)) {
+                Some(child) => entry = child,
+                None => break,
+            }
+            entries.push(entry);
+        }
+        entries
+    }
+    pub const SIMILAR_DURATION_TOLERANCE: f64 = 0.1;
+    pub const SIMILAR_SIZE_TOLERANCE: f64 = 0.2;
+
+    // sounds within the duration and size tolerances, most similar first
+    pub fn similar_sounds(&self, sound: &LibraryEntry) -> Vec<&LibraryEntry> {
+        fn difference(a: i64, b: i64) -> f64 {
+            if b == 0 {
+                return if a == 0 { 0.0 } else { f64::INFINITY };
+            }
+            (a - b).abs() as f64 / b as f64
+        }
+
+        let mut similar = self
+            .sound_effects
+            .sounds()
+            .into_iter()
+            .filter(|other| other.id() != sound.id())
+            .filter_map(|other| {
+                let duration = difference(other.duration(), sound.duration());
+                let size = difference(other.bytes(), sound.bytes());
+                (duration <= Self::SIMILAR_DURATION_TOLERANCE && size <= Self::SIMILAR_SIZE_TOLERANCE).then(|| {
+                    let score = duration / Self::SIMILAR_DURATION_TOLERANCE
+                        + size / Self::SIMILAR_SIZE_TOLERANCE;
+                    (score, other)
+                })
+            })
+            .collect::<Vec<_>>();
+        similar.sort_by(|a, b| a.0.total_cmp(&b.0));
+        similar.into_iter().map(|(_, other)| other).collect()
+    }
+    pub fn find_by_id(&self, id: i64) -> Option<&LibraryEntry> {
+        let path = self.index.get(&id)?;
+        let mut entry = &self.sound_effects;
+        for &i in path {
+            entry = entry.children()?.get(i)?;
+        }
+        Some(entry)
+    }
+}
+
+pub fn remove_partial_downloads() {
+    if let Ok(readdir) = GD_FOLDER.read_dir() {
+        for file in readdir.flatten() {
+            let path = file.path();
+            if path.extension().is_some_and(|ext| ext == PARTIAL_DOWNLOAD_EXTENSION) {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+// malformed entries are skipped by parse_string, but it needs a root to build on
+fn validate_library_string(string: &str) -> Result<(), String> {
+    let (sound_effects, _) = string.split_once('|').unwrap_or((string, ""));
+    if !sound_effects.split(';').any(|line| LibraryEntry::parse_line(line).is_some()) {
+        return Err("the library contains no entries".to_string());
+    }
+    Ok(())
+}
+
+pub fn try_parse_library(data: &[u8]) -> Result<Library, String> {
+    let data = try_full_decode(data.trim_ascii())?;
+    let string = std::str::from_utf8(&data).map_err(|e| format!("invalid utf-8: {e}"))?;
+    validate_library_string(string)?;
+    Ok(Library::parse_string(string))
 }
 
-pub fn parse_library(data: &[u8]) -> Library {
-    let data: Vec<u8> = full_decode(data);
-    let string = std::str::from_utf8(&data).unwrap();
-    Library::parse_string(string)
+#[cfg(test)]
+mod tests {
+    use crate::{stats::EXISTING_SOUND_FILES, util::LOCAL_SFX_LIBRARY};
+
+    use super::{LibraryEntry, SoundEntry};
+
+    #[test]
+    fn exists_follows_download_and_delete() {
+        let id = 910_000;
+        LOCAL_SFX_LIBRARY.lock().insert(id, vec![0; 16]);
+        let sound = LibraryEntry::Sound(SoundEntry {
+            id,
+            name: "test".to_string(),
+            parent: 1,
+            bytes: 16,
+            duration: 100,
+            original_index: 0,
+        });
+
+        sound.download_and_store();
+        assert!(EXISTING_SOUND_FILES.lock().unwrap().contains(&id));
+        assert!(sound.exists());
+
+        sound.delete();
+        assert!(!EXISTING_SOUND_FILES.lock().unwrap().contains(&id));
+        assert!(!sound.exists());
+    }
 }
@@ -0,0 +1,81 @@
+use std::{path::PathBuf, sync::{Arc, Mutex}, fs};
+
+use eframe::epaint::ahash::{HashSet, HashSetExt};
+use lazy_static::lazy_static;
+
+use crate::{gui::LibraryVersion, library::Library, util::GD_FOLDER, encoding::{full_decode, full_encode}};
+
+pub const MAX_RECORDED_VERSIONS: usize = 20;
+
+// (version, ids of every entry in that version), oldest first
+pub type LibraryHistory = Vec<(LibraryVersion, Vec<i64>)>;
+
+lazy_static!{
+    pub static ref LIBRARY_HISTORY_FILE: PathBuf = GD_FOLDER.join("gdsfx_library_history.dat");
+    pub static ref LIBRARY_HISTORY: Arc<Mutex<LibraryHistory>> = Arc::new(Mutex::new(read_file()));
+}
+
+pub fn read_file() -> LibraryHistory {
+    if LIBRARY_HISTORY_FILE.exists() {
+        let data = fs::read(LIBRARY_HISTORY_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save() {
+    let string = serde_json::to_string(&*LIBRARY_HISTORY.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(LIBRARY_HISTORY_FILE.as_path(), data).unwrap();
+}
+
+// the root category is named after the library version
+pub fn library_version(library: &Library) -> Option<LibraryVersion> {
+    LibraryVersion::try_from(library.sound_effects.name()).ok()
+}
+
+pub fn record_library(library: &Library) {
+    let Some(version) = library_version(library) else {
+        return;
+    };
+
+    let mut history = LIBRARY_HISTORY.lock().unwrap();
+    if history.iter().any(|(recorded, _)| *recorded == version) {
+        return;
+    }
+
+    let mut ids = library.index.keys().copied().filter(|id| *id >= 0).collect::<Vec<_>>();
+    ids.sort_unstable();
+    history.push((version, ids));
+    history.sort_by_key(|(version, _)| *version);
+    let excess = history.len().saturating_sub(MAX_RECORDED_VERSIONS);
+    history.drain(..excess);
+    drop(history);
+    save();
+}
+
+// ids in the given library that didn't exist `versions` recorded versions ago
+pub fn added_since(library: &Library, versions: usize) -> HashSet<i64> {
+    let (Some(version), true) = (library_version(library), versions > 0) else {
+        return HashSet::new();
+    };
+
+    let history = LIBRARY_HISTORY.lock().unwrap();
+    let older = history
+        .iter()
+        .filter(|(recorded, _)| *recorded < version)
+        .collect::<Vec<_>>();
+    let Some((_, baseline)) = older.get(older.len().saturating_sub(versions)) else {
+        return HashSet::new();
+    };
+    let baseline = baseline.iter().collect::<HashSet<_>>();
+
+    library
+        .index
+        .keys()
+        .filter(|id| **id >= 0 && !baseline.contains(id))
+        .copied()
+        .collect()
+}
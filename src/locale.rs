@@ -0,0 +1,95 @@
+use eframe::{egui::{self, FontData, FontDefinitions, FontFamily}, epaint::ahash::HashMap};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use crate::settings::SETTINGS;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+pub enum Locale {
+    #[default]
+    English,
+    French,
+    German,
+    Japanese,
+}
+
+impl Locale {
+    // shown in the locale's own language
+    pub fn name(self) -> &'static str {
+        match self {
+            Locale::English => "English",
+            Locale::French => "Français",
+            Locale::German => "Deutsch",
+            Locale::Japanese => "日本語",
+        }
+    }
+
+    fn strings(self) -> &'static HashMap<String, String> {
+        match self {
+            Locale::English => &ENGLISH,
+            Locale::French => &FRENCH,
+            Locale::German => &GERMAN,
+            Locale::Japanese => &JAPANESE,
+        }
+    }
+}
+
+fn parse(json: &str) -> HashMap<String, String> {
+    serde_json::from_str(json).expect("invalid locale file")
+}
+
+lazy_static!{
+    static ref ENGLISH: HashMap<String, String> = parse(include_str!("locales/en.json"));
+    static ref FRENCH: HashMap<String, String> = parse(include_str!("locales/fr.json"));
+    static ref GERMAN: HashMap<String, String> = parse(include_str!("locales/de.json"));
+    static ref JAPANESE: HashMap<String, String> = parse(include_str!("locales/ja.json"));
+}
+
+// falls back to english, then to the key itself
+pub fn translate(key: &str) -> &str {
+    let locale = SETTINGS.lock().unwrap().locale;
+    locale
+        .strings()
+        .get(key)
+        .or_else(|| ENGLISH.get(key))
+        .map(String::as_str)
+        .unwrap_or(key)
+}
+
+// egui's default fonts have no japanese glyphs, so a system font is used as a fallback if there is one
+const CJK_FONT_PATHS: [&str; 6] = [
+    "C:\\Windows\\Fonts\\YuGothM.ttc",
+    "C:\\Windows\\Fonts\\msgothic.ttc",
+    "/System/Library/Fonts/ヒラギノ角ゴシック W3.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+];
+
+pub fn install_fallback_fonts(ctx: &egui::Context) {
+    let Some(data) = CJK_FONT_PATHS.iter().find_map(|path| std::fs::read(path).ok()) else {
+        return;
+    };
+    let mut fonts = FontDefinitions::default();
+    fonts.font_data.insert("cjk".to_string(), FontData::from_owned(data));
+    for family in [FontFamily::Proportional, FontFamily::Monospace] {
+        fonts.families.entry(family).or_default().push("cjk".to_string());
+    }
+    ctx.set_fonts(fonts);
+}
+
+// `t!("key")` translates a key, `t!("key", name = value)` also fills in `{name}` placeholders
+macro_rules! t {
+    ($key:literal) => {
+        $crate::locale::translate($key)
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut text = $crate::locale::translate($key).to_string();
+        $(
+            text = text.replace(concat!("{", stringify!($name), "}"), &$value.to_string());
+        )+
+        text
+    }};
+}
+pub(crate) use t;
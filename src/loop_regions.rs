@@ -0,0 +1,47 @@
+use std::{path::PathBuf, sync::{Arc, Mutex}, fs};
+
+use eframe::epaint::ahash::HashMap;
+use lazy_static::lazy_static;
+
+use crate::{autosave::Autosave, util::GD_FOLDER, encoding::{full_decode, full_encode}};
+
+pub const MIN_LOOP_LENGTH: i64 = 5; // in centiseconds, like the durations
+
+lazy_static!{
+    pub static ref LOOP_REGIONS_FILE: PathBuf = GD_FOLDER.join("gdsfx_loop_regions.dat");
+    // sound id -> (start, end) in centiseconds
+    pub static ref LOOP_REGIONS: Arc<Mutex<HashMap<i64, (i64, i64)>>> = Arc::new(Mutex::new(read_file()));
+}
+
+pub static AUTOSAVE: Autosave = Autosave::new(save);
+
+pub fn read_file() -> HashMap<i64, (i64, i64)> {
+    if LOOP_REGIONS_FILE.exists() {
+        let data = fs::read(LOOP_REGIONS_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        HashMap::default()
+    }
+}
+
+pub fn save() {
+    let string = serde_json::to_string(&*LOOP_REGIONS.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(LOOP_REGIONS_FILE.as_path(), data).unwrap();
+}
+
+pub fn get_loop_region(id: i64) -> Option<(i64, i64)> {
+    LOOP_REGIONS.lock().unwrap().get(&id).copied()
+}
+
+// None clears the region
+pub fn set_loop_region(id: i64, region: Option<(i64, i64)>) {
+    let mut regions = LOOP_REGIONS.lock().unwrap();
+    match region {
+        Some(region) => regions.insert(id, region),
+        None => regions.remove(&id),
+    };
+    drop(regions);
+    AUTOSAVE.mark_dirty();
+}
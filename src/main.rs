@@ -1,31 +1,70 @@
 use eframe::{NativeOptions, egui::ViewportBuilder, epaint::Vec2, Theme};
+use autosave::start_autosave;
+use bandwidth::start_session;
 use stats::check_all_sfx_files;
-use util::{hide_console_window, TOTAL_WIDTH, TOTAL_HEIGHT};
+use updates::check_for_updates;
+use util::hide_console_window;
 
+mod args;
 mod requests;
 mod encoding;
+mod export;
 mod library;
+mod library_history;
 mod gui;
 mod util;
 mod audio;
 mod favourites;
+mod autosave;
+mod bandwidth;
+mod collections;
+mod notes;
+mod usage;
+mod play_history;
+mod tags;
+mod ratings;
+mod loop_regions;
+mod hidden;
+mod annotations;
+mod color_rules;
+mod keybindings;
+mod locale;
+mod theme;
+mod filters;
+mod presets;
+mod search_history;
 mod stats;
+mod failed_downloads;
+mod settings;
+mod updates;
+mod notifications;
+mod crash_recovery;
+mod diagnostics;
 
 fn main() {
     hide_console_window();
+    crash_recovery::install_panic_hook();
 
     check_all_sfx_files();
+    check_for_updates();
+    start_autosave();
+    start_session();
 
-    let mut gdsfx = gui::GdSfx::default();
+    // the library is loaded in the background once the window is open
+    let gdsfx = gui::GdSfx::new();
 
-    gdsfx.get_cdn_url(false);
-    gdsfx.get_sfx_version(false);
-    gdsfx.get_sfx_library(false);
+    let settings = settings::get_settings();
+    let mut viewport = ViewportBuilder::default()
+        .with_min_inner_size(Vec2 {x: settings.min_window_width, y: settings.min_window_height});
+    if let Some((width, height)) = settings.max_window_size {
+        viewport = viewport.with_max_inner_size(Vec2 {x: width, y: height});
+    }
+    if let Some((width, height)) = settings.last_window_size {
+        viewport = viewport.with_inner_size(Vec2 {x: width, y: height});
+    }
 
     gdsfx.run(NativeOptions {
-        viewport: ViewportBuilder::default()
-            .with_min_inner_size(Vec2 {x: TOTAL_WIDTH, y: TOTAL_HEIGHT}),
-            
+        viewport,
         follow_system_theme: false,
         default_theme: Theme::Dark,
 
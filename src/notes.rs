@@ -0,0 +1,44 @@
+use std::{path::PathBuf, sync::{Arc, Mutex}, fs};
+
+use eframe::epaint::ahash::HashMap;
+use lazy_static::lazy_static;
+
+use crate::{autosave::Autosave, util::GD_FOLDER, encoding::{full_decode, full_encode}};
+
+lazy_static!{
+    pub static ref NOTES_FILE: PathBuf = GD_FOLDER.join("gdsfx_notes.dat");
+    pub static ref CATEGORY_NOTES: Arc<Mutex<HashMap<i64, String>>> = Arc::new(Mutex::new(read_file()));
+}
+
+pub static AUTOSAVE: Autosave = Autosave::new(save);
+
+pub fn read_file() -> HashMap<i64, String> {
+    if NOTES_FILE.exists() {
+        let data = fs::read(NOTES_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        HashMap::default()
+    }
+}
+
+pub fn save() {
+    let string = serde_json::to_string(&*CATEGORY_NOTES.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(NOTES_FILE.as_path(), data).unwrap();
+}
+
+pub fn get_category_note(id: i64) -> Option<String> {
+    CATEGORY_NOTES.lock().unwrap().get(&id).cloned()
+}
+
+pub fn set_category_note(id: i64, note: &str) {
+    let mut notes = CATEGORY_NOTES.lock().unwrap();
+    if note.is_empty() {
+        notes.remove(&id);
+    } else {
+        notes.insert(id, note.to_string());
+    }
+    drop(notes);
+    AUTOSAVE.mark_dirty();
+}
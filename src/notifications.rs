@@ -0,0 +1,57 @@
+use std::{collections::VecDeque, sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+use lazy_static::lazy_static;
+
+pub const NOTIFICATION_DURATION: Duration = Duration::from_secs(5);
+pub const ERROR_LOG_LENGTH: usize = 20;
+
+lazy_static!{
+    pub static ref NOTIFICATIONS: Arc<Mutex<Vec<Notification>>> = Default::default();
+    // errors outlive their notification here, for diagnostics
+    pub static ref ERROR_LOG: Arc<Mutex<VecDeque<String>>> = Default::default();
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub message: String,
+    pub is_error: bool,
+    pub created: Instant,
+}
+
+pub fn push_notification(message: impl ToString) {
+    NOTIFICATIONS.lock().unwrap().push(Notification {
+        message: message.to_string(),
+        is_error: false,
+        created: Instant::now(),
+    });
+}
+
+pub fn push_error(message: impl ToString) {
+    let message = message.to_string();
+    log_error(message.clone());
+
+    NOTIFICATIONS.lock().unwrap().push(Notification {
+        message,
+        is_error: true,
+        created: Instant::now(),
+    });
+}
+
+// only kept for diagnostics, for errors that would flood the notifications
+pub fn log_error(message: impl ToString) {
+    let mut log = ERROR_LOG.lock().unwrap();
+    if log.len() == ERROR_LOG_LENGTH {
+        log.pop_front();
+    }
+    log.push_back(message.to_string());
+}
+
+pub fn active_notifications() -> Vec<Notification> {
+    let mut notifications = NOTIFICATIONS.lock().unwrap();
+    notifications.retain(|n| n.created.elapsed() < NOTIFICATION_DURATION);
+    notifications.clone()
+}
+
+pub fn recent_errors() -> Vec<String> {
+    ERROR_LOG.lock().unwrap().iter().cloned().collect()
+}
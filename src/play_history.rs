@@ -0,0 +1,33 @@
+use std::{sync::{Arc, Mutex}, time::{SystemTime, UNIX_EPOCH}};
+
+use lazy_static::lazy_static;
+
+use crate::library::LibraryEntry;
+
+lazy_static!{
+    // only kept for the current session, oldest first
+    pub static ref PLAY_HISTORY: Arc<Mutex<Vec<PlayRecord>>> = Default::default();
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayRecord {
+    pub id: i64,
+    pub name: String,
+    pub played_at: u64, // unix timestamp in seconds
+}
+
+pub fn record_play(sfx: &LibraryEntry) {
+    let played_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    PLAY_HISTORY.lock().unwrap().push(PlayRecord {
+        id: sfx.id(),
+        name: sfx.name().to_string(),
+        played_at,
+    });
+}
+
+pub fn get_play_history() -> Vec<PlayRecord> {
+    PLAY_HISTORY.lock().unwrap().clone()
+}
@@ -0,0 +1,25 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{filters::Filters, gui::Sorting};
+
+// a shareable snapshot of the library view, plain json so it can be sent around.
+// missing fields fall back to their defaults and unknown ones are ignored,
+// so presets from other versions still load
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewPreset {
+    pub query: String,
+    pub sorting: Sorting,
+    pub filters: Filters, // includes the tag and collection
+}
+
+pub fn save_preset(preset: &ViewPreset, path: &Path) -> io::Result<()> {
+    fs::write(path, serde_json::to_string_pretty(preset).unwrap())
+}
+
+pub fn load_preset(path: &Path) -> Result<ViewPreset, String> {
+    let data = fs::read(path).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&data).map_err(|e| e.to_string())
+}
@@ -0,0 +1,55 @@
+use std::{path::PathBuf, sync::{Arc, Mutex}, fs};
+
+use eframe::epaint::ahash::HashMap;
+use lazy_static::lazy_static;
+
+use crate::{autosave::Autosave, util::GD_FOLDER, encoding::{full_decode, full_encode}};
+
+pub const MAX_RATING: u8 = 5;
+
+lazy_static!{
+    pub static ref RATINGS_FILE: PathBuf = GD_FOLDER.join("gdsfx_ratings.dat");
+    // sound id -> 1 to MAX_RATING
+    pub static ref RATINGS: Arc<Mutex<HashMap<i64, u8>>> = Arc::new(Mutex::new(read_file()));
+}
+
+pub static AUTOSAVE: Autosave = Autosave::new(save);
+
+pub fn read_file() -> HashMap<i64, u8> {
+    if RATINGS_FILE.exists() {
+        let data = fs::read(RATINGS_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        HashMap::default()
+    }
+}
+
+pub fn save() {
+    let string = serde_json::to_string(&*RATINGS.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(RATINGS_FILE.as_path(), data).unwrap();
+}
+
+pub fn get_rating(id: i64) -> Option<u8> {
+    RATINGS.lock().unwrap().get(&id).copied()
+}
+
+// None clears the rating
+pub fn set_rating(id: i64, rating: Option<u8>) {
+    let mut ratings = RATINGS.lock().unwrap();
+    match rating {
+        Some(rating) => ratings.insert(id, rating.clamp(1, MAX_RATING)),
+        None => ratings.remove(&id),
+    };
+    drop(ratings);
+    AUTOSAVE.mark_dirty();
+}
+
+pub fn set_ratings(ratings: &[(i64, u8)]) {
+    RATINGS
+        .lock()
+        .unwrap()
+        .extend(ratings.iter().map(|(id, rating)| (*id, (*rating).clamp(1, MAX_RATING))));
+    AUTOSAVE.mark_dirty();
+}
@@ -1,13 +1,20 @@
 // DISCALIMER: this file contains the worst code and mispells you've ever seen
 // make sure to contribute to fix that
 
-use std::fs;
+use std::{fs, sync::atomic::{AtomicBool, Ordering}};
 
 use reqwest::blocking::Client;
 use reqwest::header::{CONTENT_TYPE, USER_AGENT};
-
-use crate::gui::{GdSfx, VersionType};
-use crate::library::{parse_library, LibraryEntry, Library};
+use serde::Deserialize;
+
+use crate::args::ARGS;
+use crate::bandwidth::record_download;
+use crate::gui::{GdSfx, LibraryVersion};
+use crate::library::{try_parse_library, LibraryEntry, Library};
+use crate::library_history::library_version;
+use crate::locale::t;
+use crate::notifications::push_error;
+use crate::settings::{is_offline, is_safe_mode};
 use crate::util::SFX_LIBRARY_FILE;
 
 pub const GET_CUSTOM_CONTENT_URL: &str =
@@ -15,6 +22,14 @@ pub const GET_CUSTOM_CONTENT_URL: &str =
 pub const CDN_URL: &str = "https://geometrydashfiles.b-cdn.net";
 pub const ENDPOINT_SFX_VERSION: &str = "sfx/sfxlibrary_version.txt";
 pub const ENDPOINT_SFX_LIBRARY: &str = "sfx/sfxlibrary.dat";
+pub const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/SpeckyYT/gd_sfx/releases/latest";
+
+// set when the library couldn't be fetched and the last downloaded one is shown instead
+static USING_CACHED_LIBRARY: AtomicBool = AtomicBool::new(false);
+
+pub fn using_cached_library() -> bool {
+    USING_CACHED_LIBRARY.load(Ordering::Relaxed)
+}
 
 impl GdSfx {
     pub fn get_cdn_url(&mut self, force: bool) -> Option<&String> {
@@ -22,6 +37,10 @@ impl GdSfx {
             return self.cdn_url.as_ref();
         }
 
+        if is_offline() {
+            return None;
+        }
+
         let request = Client::default()
             .post(GET_CUSTOM_CONTENT_URL)
             .header(USER_AGENT, "")
@@ -44,11 +63,15 @@ impl GdSfx {
     }
 
     #[allow(unused)]
-    pub fn get_sfx_version(&mut self, force: bool) -> Option<VersionType> {
+    pub fn get_sfx_version(&mut self, force: bool) -> Option<LibraryVersion> {
         if !force && self.sfx_version.is_some() {
             return self.sfx_version;
         }
 
+        if is_offline() {
+            return None;
+        }
+
         let cdn_url = self.get_cdn_url(force)?;
 
         let output = Client::default()
@@ -56,9 +79,8 @@ impl GdSfx {
             .send()
             .ok()?
             .text()
-            .ok()?
-            .parse()
-            .ok();
+            .ok()
+            .and_then(|text| LibraryVersion::try_from(text).ok());
 
         self.sfx_version = output;
 
@@ -66,52 +88,143 @@ impl GdSfx {
     }
 
     pub fn get_sfx_library(&mut self, force: bool) -> Option<&Library> {
-        let root = if !force && SFX_LIBRARY_FILE.exists() {
-            let sfx_data = fs::read(SFX_LIBRARY_FILE.as_path()).unwrap();
-            let root = parse_library(&sfx_data);
-
-            if self
-                .sfx_version
-                .map(|ver| ver.to_string() == root.sound_effects.name())
-                .unwrap_or(false)
-            {
+        if is_safe_mode() {
+            return None; // the cache might be what keeps the app from starting
+        }
+
+        if let Some(path) = ARGS.library_file.as_ref() {
+            let library = fs::read(path)
+                .map_err(|e| e.to_string())
+                .and_then(|data| try_parse_library(&data));
+            match library {
+                Ok(library) => {
+                    self.sfx_version = library_version(&library).or(self.sfx_version);
+                    self.sfx_library = Some(library);
+                }
+                Err(error) => {
+                    let message = format!("Couldn't load library file {}: {error}", path.display());
+                    push_error(message);
+                }
+            }
+            return self.sfx_library.as_ref();
+        }
+
+        if is_offline() {
+            // only the cached library can be used
+            if SFX_LIBRARY_FILE.exists() {
+                self.sfx_library = read_cached_library();
+            }
+            return self.sfx_library.as_ref();
+        }
+
+        if !force && SFX_LIBRARY_FILE.exists() {
+            let root = read_cached_library()
+                .filter(|root| self.sfx_version.is_some_and(|ver| library_version(root) == Some(ver)));
+
+            if let Some(root) = root {
+                USING_CACHED_LIBRARY.store(false, Ordering::Relaxed);
                 self.sfx_library = Some(root);
                 return self.sfx_library.as_ref();
-            } else {
-                download_and_parse_library(self.get_cdn_url(false)?)
             }
-        } else {
-            download_and_parse_library(self.get_cdn_url(false)?)
-        };
-        self.sfx_library = Some(root);
+        }
+
+        let cdn_url = self.get_cdn_url(false).cloned();
+        match cdn_url.and_then(|cdn_url| download_and_parse_library(&cdn_url)) {
+            Some(root) => {
+                USING_CACHED_LIBRARY.store(false, Ordering::Relaxed);
+                self.sfx_library = Some(root);
+            }
+            // the network is down, the last downloaded library beats showing nothing
+            None if SFX_LIBRARY_FILE.exists() => {
+                self.sfx_library = read_cached_library();
+                USING_CACHED_LIBRARY.store(self.sfx_library.is_some(), Ordering::Relaxed);
+            }
+            None => {}
+        }
         self.sfx_library.as_ref()
     }
 }
 
-fn download_and_parse_library(cdn_url: &str) -> Library {
+// a truncated or corrupt cache is reported instead of taking the loader down
+fn read_cached_library() -> Option<Library> {
+    let library = fs::read(SFX_LIBRARY_FILE.as_path())
+        .map_err(|e| e.to_string())
+        .and_then(|data| try_parse_library(&data));
+    match library {
+        Ok(library) => Some(library),
+        Err(error) => {
+            push_error(t!("error.cached_library", error = error));
+            None
+        }
+    }
+}
+
+// None if the request failed, a broken response doesn't replace the cached library
+fn download_and_parse_library(cdn_url: &str) -> Option<Library> {
     let client = Client::default();
 
     let sfx_data = client
         .get(format!("{cdn_url}/{ENDPOINT_SFX_LIBRARY}"))
         .send()
-        .unwrap()
+        .ok()?
         .bytes()
-        .unwrap();
+        .ok()?;
+    record_download(sfx_data.len());
 
+    let library = try_parse_library(&sfx_data).ok()?;
     fs::write(SFX_LIBRARY_FILE.as_path(), &sfx_data).unwrap();
-    parse_library(&sfx_data)
+    Some(library)
+}
+
+pub fn sfx_url(cdn_url: &str, sound: &LibraryEntry) -> String {
+    format!("{cdn_url}/sfx/{}", sound.filename())
 }
 
 pub fn download_sfx(cdn_url: &str, sound: &LibraryEntry) -> Option<Vec<u8>> {
-    let url = format!("{cdn_url}/sfx/{}", sound.filename());
+    if is_offline() {
+        return None;
+    }
 
-    Some(
-        Client::default()
-            .get(url)
-            .send()
-            .ok()?
-            .bytes()
-            .ok()?
-            .to_vec(),
-    )
+    let url = sfx_url(cdn_url, sound);
+
+    let data = Client::default()
+        .get(url)
+        .send()
+        .ok()?
+        .bytes()
+        .ok()?
+        .to_vec();
+    record_download(data.len());
+    Some(data)
+}
+
+pub fn get_community_annotations(url: &str) -> Option<String> {
+    let response = Client::default()
+        .get(url)
+        .header(USER_AGENT, "gd_sfx")
+        .send()
+        .ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.text().ok()
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+}
+
+// returns (tag, url)
+pub fn get_latest_release() -> Option<(String, String)> {
+    let release: GithubRelease = Client::default()
+        .get(LATEST_RELEASE_URL)
+        .header(USER_AGENT, "gd_sfx")
+        .send()
+        .ok()?
+        .json()
+        .ok()?;
+
+    Some((release.tag_name, release.html_url))
 }
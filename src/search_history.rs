@@ -0,0 +1,46 @@
+use std::{path::PathBuf, sync::{Arc, Mutex}, fs};
+
+use lazy_static::lazy_static;
+
+use crate::{util::GD_FOLDER, encoding::{full_decode, full_encode}};
+
+pub const MAX_SEARCH_HISTORY: usize = 20;
+
+lazy_static!{
+    pub static ref SEARCH_HISTORY_FILE: PathBuf = GD_FOLDER.join("gdsfx_search_history.dat");
+    // newest first
+    pub static ref SEARCH_HISTORY: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(read_file()));
+}
+
+pub fn read_file() -> Vec<String> {
+    if SEARCH_HISTORY_FILE.exists() {
+        let data = fs::read(SEARCH_HISTORY_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save() {
+    let string = serde_json::to_string(&*SEARCH_HISTORY.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(SEARCH_HISTORY_FILE.as_path(), data).unwrap();
+}
+
+pub fn get_search_history() -> Vec<String> {
+    SEARCH_HISTORY.lock().unwrap().clone()
+}
+
+pub fn record_search(query: &str) {
+    let query = query.trim();
+    if query.is_empty() {
+        return;
+    }
+    let mut history = SEARCH_HISTORY.lock().unwrap();
+    history.retain(|previous| previous != query);
+    history.insert(0, query.to_string());
+    history.truncate(MAX_SEARCH_HISTORY);
+    drop(history);
+    save();
+}
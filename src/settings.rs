@@ -0,0 +1,158 @@
+use std::{path::PathBuf, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}, fs};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::{args::ARGS, gui::{DetailPanelPosition, MetadataItem, ReplayBehavior, ResultLayout, Sorting}, locale::Locale, theme::AppTheme, util::{GD_FOLDER, TOTAL_WIDTH, TOTAL_HEIGHT}, encoding::{full_decode, full_encode}};
+
+lazy_static!{
+    pub static ref SETTINGS_FILE: PathBuf = GD_FOLDER.join("gdsfx_settings.dat");
+    pub static ref SETTINGS: Arc<Mutex<Settings>> = Arc::new(Mutex::new(
+        if ARGS.safe_mode { Settings::default() } else { read_file() }
+    ));
+    // starts out as --safe-mode, cleared when the user retries loading normally
+    pub static ref SAFE_MODE: AtomicBool = AtomicBool::new(ARGS.safe_mode);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub check_for_updates: bool,
+    pub last_update_check: u64, // unix timestamp in seconds
+    pub latest_release: Option<(String, String)>, // (tag, url)
+    pub offline_mode: bool,
+    pub default_sorting: Sorting,
+    pub auto_version_check_interval_mins: u64, // 0 = disabled
+    pub preview_on_hover: bool,
+    pub hover_preview_delay_ms: u64,
+    pub pinned_categories: Vec<i64>,
+    pub preview_length_secs: Option<f32>, // None = full
+    pub recently_added_versions: usize, // 0 = disabled
+    pub auto_play_on_select: bool,
+    pub double_click_to_play: bool,
+    pub replay_behavior: ReplayBehavior,
+    pub detail_panel_position: DetailPanelPosition,
+    pub result_layout: ResultLayout,
+    pub locale: Locale,
+    pub theme: AppTheme,
+    pub transliterate_search: bool,
+    pub categories_only: bool,
+    pub community_annotations_url: String,
+    pub annotations_refetch_hours: u64,
+    pub last_annotations_fetch: u64, // unix timestamp in seconds
+    pub last_annotations_url: String, // what the cached file was fetched from
+    pub min_window_width: f32,
+    pub min_window_height: f32,
+    pub max_window_size: Option<(f32, f32)>, // None = unlimited
+    pub last_window_size: Option<(f32, f32)>,
+    pub last_selected_sfx: Option<i64>,
+    pub idle_unload_mins: u64, // 0 = disabled
+    pub favourite_on_download: bool,
+    pub unfavourite_on_delete: bool,
+    pub metadata_bar: Vec<MetadataItem>, // empty = hidden
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            check_for_updates: false,
+            last_update_check: 0,
+            latest_release: None,
+            offline_mode: false,
+            default_sorting: Sorting::default(),
+            auto_version_check_interval_mins: 30,
+            preview_on_hover: false,
+            hover_preview_delay_ms: 500,
+            pinned_categories: Vec::new(),
+            preview_length_secs: None,
+            recently_added_versions: 3,
+            auto_play_on_select: false,
+            double_click_to_play: false,
+            replay_behavior: ReplayBehavior::default(),
+            detail_panel_position: DetailPanelPosition::default(),
+            result_layout: ResultLayout::default(),
+            locale: Locale::default(),
+            theme: AppTheme::default(),
+            transliterate_search: false,
+            categories_only: false,
+            community_annotations_url: String::new(), // nobody publishes one yet
+            annotations_refetch_hours: 24,
+            last_annotations_fetch: 0,
+            last_annotations_url: String::new(),
+            min_window_width: TOTAL_WIDTH,
+            min_window_height: TOTAL_HEIGHT,
+            max_window_size: None,
+            last_window_size: None,
+            last_selected_sfx: None,
+            idle_unload_mins: 0,
+            favourite_on_download: false,
+            unfavourite_on_delete: false,
+            metadata_bar: Vec::new(),
+        }
+    }
+}
+
+pub fn read_file() -> Settings {
+    if SETTINGS_FILE.exists() {
+        let data = fs::read(SETTINGS_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        Settings::default()
+    }
+}
+
+// safe mode runs on the defaults, which must never replace the saved settings
+pub fn save() {
+    if is_safe_mode() {
+        return;
+    }
+    let string = serde_json::to_string(&*SETTINGS.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(SETTINGS_FILE.as_path(), data).unwrap();
+}
+
+pub fn get_settings() -> Settings {
+    SETTINGS.lock().unwrap().clone()
+}
+
+pub fn set_settings(settings: Settings) {
+    *SETTINGS.lock().unwrap() = settings;
+    save();
+}
+
+pub fn is_offline() -> bool {
+    ARGS.offline || is_safe_mode() || SETTINGS.lock().unwrap().offline_mode
+}
+
+pub fn is_safe_mode() -> bool {
+    SAFE_MODE.load(Ordering::Relaxed)
+}
+
+// goes back to the saved settings, the network and the library cache
+pub fn leave_safe_mode() {
+    SAFE_MODE.store(false, Ordering::Relaxed);
+    *SETTINGS.lock().unwrap() = read_file();
+}
+
+pub fn categories_only() -> bool {
+    SETTINGS.lock().unwrap().categories_only
+}
+
+pub fn is_pinned(id: i64) -> bool {
+    SETTINGS.lock().unwrap().pinned_categories.contains(&id)
+}
+
+pub fn pin_category(id: i64) {
+    let mut settings = SETTINGS.lock().unwrap();
+    if !settings.pinned_categories.contains(&id) {
+        settings.pinned_categories.push(id);
+    }
+    drop(settings);
+    save();
+}
+
+pub fn unpin_category(id: i64) {
+    SETTINGS.lock().unwrap().pinned_categories.retain(|pinned| *pinned != id);
+    save();
+}
@@ -1,36 +1,378 @@
-use std::{sync::{Arc, Mutex}, thread::{spawn, JoinHandle}};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Arc, Mutex},
+    thread::{spawn, JoinHandle},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use eframe::epaint::ahash::HashSet;
+use eframe::epaint::ahash::{HashMap, HashSet};
 use lazy_static::lazy_static;
 
-use crate::util::GD_FOLDER;
+use crate::{
+    failed_downloads::add_failed_download,
+    library::{Library, LibraryEntry},
+    locale::t,
+    notifications::{push_error, push_notification},
+    util::{GD_FOLDER, SHUTTING_DOWN},
+};
 
 lazy_static!{
+    // one `timestamp,files,bytes` line per scan, only ever appended to
+    pub static ref CACHE_HISTORY_FILE: PathBuf = GD_FOLDER.join("gdsfx_cache_history.log");
     pub static ref EXISTING_SOUND_FILES: Arc<Mutex<HashSet<i64>>> = Default::default();
+    // held while sound files are written, deleted or scanned so EXISTING_SOUND_FILES matches the disk
+    pub static ref SOUND_FILES_LOCK: Mutex<()> = Mutex::new(());
+    pub static ref DOWNLOADS_IN_PROGRESS: Arc<Mutex<HashSet<i64>>> = Default::default();
+    // category id -> progress of a running download/delete all
+    pub static ref CATEGORY_PROGRESS: Arc<Mutex<HashMap<i64, CategoryProgress>>> = Default::default();
+    // (done, total) of a running re-download of outdated files
+    pub static ref REDOWNLOAD_PROGRESS: Arc<Mutex<Option<(usize, usize)>>> = Default::default();
+}
+
+// until the first scan finishes EXISTING_SOUND_FILES doesn't know about older files
+pub static SOUND_FILES_SCANNED: AtomicBool = AtomicBool::new(false);
+
+// bumped whenever EXISTING_SOUND_FILES changes so cached search results showing only downloaded sounds get refreshed
+static SOUND_FILES_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, Copy)]
+pub struct CategoryProgress {
+    pub done: usize,
+    pub total: usize,
+    pub deleting: bool,
+}
+
+fn sound_files_changed() {
+    SOUND_FILES_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn sound_files_generation() -> usize {
+    SOUND_FILES_GENERATION.load(Ordering::Relaxed)
 }
 
 pub fn add_file_to_stats(id: i64) {
     EXISTING_SOUND_FILES.lock().unwrap().insert(id);
+    sound_files_changed();
 }
 
 pub fn remove_file_from_stats(id: i64) {
     EXISTING_SOUND_FILES.lock().unwrap().remove(&id);
+    sound_files_changed();
+}
+
+pub fn is_downloading(id: i64) -> bool {
+    DOWNLOADS_IN_PROGRESS.lock().unwrap().contains(&id)
+}
+
+pub fn category_progress(id: i64) -> Option<CategoryProgress> {
+    CATEGORY_PROGRESS.lock().unwrap().get(&id).copied()
+}
+
+// (to download, already downloaded), custom sounds are never downloaded
+pub fn category_download_counts(category: &LibraryEntry) -> (usize, usize) {
+    let sounds = category.sounds().into_iter().filter(|sound| !sound.is_custom()).collect::<Vec<_>>();
+    let existing = sounds.iter().filter(|sound| sound.exists()).count();
+    (sounds.len() - existing, existing)
+}
+
+// existing files are skipped unless re-downloading them was asked for, e.g. when they might be corrupted
+pub fn download_category(category: &LibraryEntry, redownload_existing: bool) {
+    let sounds = category
+        .sounds()
+        .into_iter()
+        .filter(|sound| !sound.is_custom() && (redownload_existing || !sound.exists()) && !is_downloading(sound.id()))
+        .cloned()
+        .collect();
+    run_category_task(category, sounds, false);
+}
+
+pub fn delete_category(category: &LibraryEntry) {
+    let sounds = category
+        .sounds()
+        .into_iter()
+        .filter(|sound| !sound.is_custom() && sound.exists())
+        .cloned()
+        .collect();
+    run_category_task(category, sounds, true);
+}
+
+fn run_category_task(category: &LibraryEntry, sounds: Vec<LibraryEntry>, deleting: bool) {
+    let id = category.id();
+    let name = category.name().to_string();
+    if sounds.is_empty() {
+        return;
+    }
+    {
+        let mut progress = CATEGORY_PROGRESS.lock().unwrap();
+        if progress.contains_key(&id) {
+            return; // already running
+        }
+        progress.insert(id, CategoryProgress { done: 0, total: sounds.len(), deleting });
+    }
+    if !deleting {
+        DOWNLOADS_IN_PROGRESS.lock().unwrap().extend(sounds.iter().map(|sound| sound.id()));
+    }
+
+    spawn(move || {
+        let total = sounds.len();
+        let mut failed = 0;
+        for sound in &sounds {
+            if SHUTTING_DOWN.load(Ordering::Relaxed) {
+                break;
+            }
+            if deleting {
+                sound.delete();
+            } else {
+                // existing files are only queued if re-downloading them was asked for
+                let stored = if sound.exists() {
+                    sound.redownload()
+                } else {
+                    sound.download_and_store();
+                    sound.exists()
+                };
+                DOWNLOADS_IN_PROGRESS.lock().unwrap().remove(&sound.id());
+                if !stored {
+                    failed += 1;
+                }
+                // the retry queue only fetches missing files, a kept old file isn't one
+                if !sound.exists() {
+                    add_failed_download(sound.id());
+                }
+            }
+            if let Some(progress) = CATEGORY_PROGRESS.lock().unwrap().get_mut(&id) {
+                progress.done += 1;
+            }
+        }
+        // anything skipped because of a shutdown is no longer in flight
+        let mut in_progress = DOWNLOADS_IN_PROGRESS.lock().unwrap();
+        for sound in &sounds {
+            in_progress.remove(&sound.id());
+        }
+        drop(in_progress);
+        CATEGORY_PROGRESS.lock().unwrap().remove(&id);
+
+        if deleting {
+            push_notification(t!("category.deleted_all", name = name, count = total));
+        } else if failed > 0 {
+            push_error(t!("category.download_failed", name = name, failed = failed, total = total));
+        } else {
+            push_notification(t!("category.downloaded_all", name = name, count = total));
+        }
+    });
+}
+
+// downloaded sounds whose file size doesn't match the library anymore, usually after a gd update
+pub fn outdated_sounds(root: &LibraryEntry) -> Vec<LibraryEntry> {
+    root.sounds()
+        .into_iter()
+        .filter(|sound| !sound.is_custom() && sound.exists())
+        .filter(|sound| {
+            fs::metadata(sound.path()).is_ok_and(|metadata| metadata.len() != sound.bytes() as u64)
+        })
+        .cloned()
+        .collect()
+}
+
+pub fn redownload_progress() -> Option<(usize, usize)> {
+    *REDOWNLOAD_PROGRESS.lock().unwrap()
+}
+
+pub fn redownload_sounds(sounds: Vec<LibraryEntry>) {
+    {
+        let mut progress = REDOWNLOAD_PROGRESS.lock().unwrap();
+        if progress.is_some() || sounds.is_empty() {
+            return;
+        }
+        *progress = Some((0, sounds.len()));
+    }
+    DOWNLOADS_IN_PROGRESS.lock().unwrap().extend(sounds.iter().map(|sound| sound.id()));
+
+    spawn(move || {
+        let mut failed = 0;
+        for sound in &sounds {
+            if SHUTTING_DOWN.load(Ordering::Relaxed) {
+                break;
+            }
+            let replaced = sound.redownload();
+            DOWNLOADS_IN_PROGRESS.lock().unwrap().remove(&sound.id());
+            if !replaced {
+                failed += 1;
+            }
+            if !sound.exists() {
+                add_failed_download(sound.id());
+            }
+            if let Some((done, _)) = REDOWNLOAD_PROGRESS.lock().unwrap().as_mut() {
+                *done += 1;
+            }
+        }
+        let mut in_progress = DOWNLOADS_IN_PROGRESS.lock().unwrap();
+        for sound in &sounds {
+            in_progress.remove(&sound.id());
+        }
+        drop(in_progress);
+        *REDOWNLOAD_PROGRESS.lock().unwrap() = None;
+
+        if failed > 0 {
+            push_error(t!("outdated.redownload_failed", failed = failed, total = sounds.len()));
+        } else {
+            push_notification(t!("outdated.redownloaded", count = sounds.len()));
+        }
+    });
+}
+
+// downloaded files whose id isn't in the library anymore, e.g. left over from an older version
+pub fn orphaned_files(library: &Library) -> Vec<i64> {
+    let mut orphans = EXISTING_SOUND_FILES
+        .lock()
+        .unwrap()
+        .iter()
+        .copied()
+        .filter(|id| library.find_by_id(*id).is_none())
+        .collect::<Vec<_>>();
+    orphans.sort_unstable();
+    orphans
+}
+
+fn orphan_path(id: i64) -> PathBuf {
+    GD_FOLDER.join(format!("s{id}.ogg"))
+}
+
+pub fn orphaned_size(ids: &[i64]) -> u64 {
+    ids.iter()
+        .filter_map(|id| fs::metadata(orphan_path(*id)).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+// deleting many files at once would stall the gui
+pub fn delete_orphaned_files(ids: Vec<i64>) -> JoinHandle<()> {
+    spawn(move || {
+        let _lock = SOUND_FILES_LOCK.lock().unwrap();
+        let mut existing = EXISTING_SOUND_FILES.lock().unwrap();
+        let mut deleted = 0;
+        for id in &ids {
+            if fs::remove_file(orphan_path(*id)).is_ok() {
+                existing.remove(id);
+                deleted += 1;
+            }
+        }
+        drop(existing);
+        sound_files_changed();
+        push_notification(t!("orphaned.deleted", count = deleted));
+    })
 }
 
 pub fn check_all_sfx_files() -> JoinHandle<()> {
     spawn(|| {
+        let _lock = SOUND_FILES_LOCK.lock().unwrap();
+        let mut existing = HashSet::default();
+        let mut total_bytes = 0;
+
         if let Ok(readdir) = GD_FOLDER.read_dir() {
             for file in readdir.flatten() {
                 let path = file.path();
 
-                let string = path.file_name().unwrap().to_str().unwrap();
+                let Some(string) = path.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
 
                 if string.starts_with('s') && string.ends_with(".ogg") {
                     let sliced = &string[1..string.len()-4];
-                    let parsed = sliced.parse().unwrap();
-                    add_file_to_stats(parsed);
+                    if let Ok(parsed) = sliced.parse() {
+                        existing.insert(parsed);
+                        total_bytes += file.metadata().map(|m| m.len()).unwrap_or(0);
+                    }
                 }
             }
         }
+
+        record_cache_size(existing.len(), total_bytes);
+
+        // replace rather than merge, so files deleted since the last scan are dropped
+        *EXISTING_SOUND_FILES.lock().unwrap() = existing;
+        sound_files_changed();
+        SOUND_FILES_SCANNED.store(true, Ordering::Relaxed);
     })
 }
+
+fn record_cache_size(files: usize, bytes: u64) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let file = OpenOptions::new().create(true).append(true).open(CACHE_HISTORY_FILE.as_path());
+    if let Ok(mut file) = file {
+        let _ = writeln!(file, "{now},{files},{bytes}");
+    }
+}
+
+// (unix timestamp in seconds, files, bytes), oldest first
+pub fn cache_history() -> Vec<(u64, usize, u64)> {
+    let Ok(log) = fs::read_to_string(CACHE_HISTORY_FILE.as_path()) else {
+        return Vec::new();
+    };
+    log.lines()
+        .filter_map(|line| {
+            let mut parts = line.split(',');
+            let timestamp = parts.next()?.parse().ok()?;
+            let files = parts.next()?.parse().ok()?;
+            let bytes = parts.next()?.parse().ok()?;
+            Some((timestamp, files, bytes))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::spawn;
+
+    use crate::{library::{LibraryEntry, SoundEntry}, util::LOCAL_SFX_LIBRARY};
+
+    use super::{check_all_sfx_files, EXISTING_SOUND_FILES};
+
+    fn test_sound(id: i64) -> LibraryEntry {
+        // preloaded so storing it never goes to the cdn
+        LOCAL_SFX_LIBRARY.lock().insert(id, vec![0; 16]);
+        LibraryEntry::Sound(SoundEntry {
+            id,
+            name: format!("test {id}"),
+            parent: 1,
+            bytes: 16,
+            duration: 100,
+            original_index: 0,
+        })
+    }
+
+    #[test]
+    fn scan_during_downloads_matches_disk() {
+        let sounds = (900_000..900_040).map(test_sound).collect::<Vec<_>>();
+
+        let workers = sounds
+            .chunks(10)
+            .map(|chunk| chunk.to_vec())
+            .map(|chunk| {
+                spawn(move || {
+                    for (i, sound) in chunk.iter().enumerate() {
+                        sound.download_and_store();
+                        if i % 3 == 0 {
+                            sound.delete();
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let scans = (0..5).map(|_| check_all_sfx_files()).collect::<Vec<_>>();
+
+        for handle in workers.into_iter().chain(scans) {
+            handle.join().unwrap();
+        }
+
+        let existing = EXISTING_SOUND_FILES.lock().unwrap();
+        for (i, sound) in sounds.iter().enumerate() {
+            assert_eq!(sound.path().exists(), i % 10 % 3 != 0, "{sound}");
+            assert_eq!(existing.contains(&sound.id()), sound.path().exists(), "{sound}");
+        }
+    }
+}
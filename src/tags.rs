@@ -0,0 +1,182 @@
+use std::{path::PathBuf, sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex}, fs};
+
+use eframe::epaint::{ahash::HashMap, Color32};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    autosave::Autosave,
+    color_rules::{deserialize_color, serialize_color},
+    util::GD_FOLDER,
+    encoding::{full_decode, full_encode},
+};
+
+lazy_static!{
+    pub static ref TAGS_FILE: PathBuf = GD_FOLDER.join("gdsfx_tags.dat");
+    // sound id -> tags
+    pub static ref TAGS: Arc<Mutex<HashMap<i64, Vec<String>>>> = Arc::new(Mutex::new(read_file()));
+
+    pub static ref TAG_PALETTE_FILE: PathBuf = GD_FOLDER.join("gdsfx_tag_palette.dat");
+    // user defined tags and their colors, tags only used on sounds aren't in here
+    pub static ref TAG_PALETTE: Arc<Mutex<Vec<PaletteTag>>> = Arc::new(Mutex::new(read_palette_file()));
+}
+
+pub const DEFAULT_TAG_COLOR: Color32 = Color32::from_rgb(90, 90, 90);
+
+// bumped on every change so cached search results filtering by tag get refreshed
+static TAGS_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaletteTag {
+    pub name: String,
+    #[serde(serialize_with = "serialize_color", deserialize_with = "deserialize_color")]
+    pub color: Color32,
+}
+
+pub static AUTOSAVE: Autosave = Autosave::new(save);
+
+pub fn read_file() -> HashMap<i64, Vec<String>> {
+    if TAGS_FILE.exists() {
+        let data = fs::read(TAGS_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        HashMap::default()
+    }
+}
+
+pub fn save() {
+    let string = serde_json::to_string(&*TAGS.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(TAGS_FILE.as_path(), data).unwrap();
+}
+
+pub fn read_palette_file() -> Vec<PaletteTag> {
+    if TAG_PALETTE_FILE.exists() {
+        let data = fs::read(TAG_PALETTE_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save_palette() {
+    let string = serde_json::to_string(&*TAG_PALETTE.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(TAG_PALETTE_FILE.as_path(), data).unwrap();
+}
+
+fn tags_changed() {
+    TAGS_GENERATION.fetch_add(1, Ordering::Relaxed);
+    AUTOSAVE.mark_dirty();
+}
+
+pub fn tags_generation() -> usize {
+    TAGS_GENERATION.load(Ordering::Relaxed)
+}
+
+pub fn get_tags(id: i64) -> Vec<String> {
+    TAGS.lock().unwrap().get(&id).cloned().unwrap_or_default()
+}
+
+// every tag in use, sorted
+pub fn all_tags() -> Vec<String> {
+    let mut tags = TAGS.lock().unwrap().values().flatten().cloned().collect::<Vec<_>>();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+// returns the ids that didn't have the tag yet, so the change can be undone
+pub fn add_tags(ids: &[i64], tag: &str) -> Vec<i64> {
+    let mut tags = TAGS.lock().unwrap();
+    let mut added = Vec::new();
+    for id in ids {
+        let sound_tags = tags.entry(*id).or_default();
+        if !sound_tags.iter().any(|t| t == tag) {
+            sound_tags.push(tag.to_string());
+            added.push(*id);
+        }
+    }
+    drop(tags);
+    tags_changed();
+    added
+}
+
+pub fn add_tag(id: i64, tag: &str) {
+    add_tags(&[id], tag);
+}
+
+pub fn remove_tags(ids: &[i64], tag: &str) {
+    let mut tags = TAGS.lock().unwrap();
+    for id in ids {
+        if let Some(sound_tags) = tags.get_mut(id) {
+            sound_tags.retain(|t| t != tag);
+            if sound_tags.is_empty() {
+                tags.remove(id);
+            }
+        }
+    }
+    drop(tags);
+    tags_changed();
+}
+
+pub fn remove_tag(id: i64, tag: &str) {
+    remove_tags(&[id], tag);
+}
+
+// the user defined tags followed by the other tags in use
+pub fn palette_tags() -> Vec<PaletteTag> {
+    let mut palette = TAG_PALETTE.lock().unwrap().clone();
+    for tag in all_tags() {
+        if !palette.iter().any(|defined| defined.name == tag) {
+            palette.push(PaletteTag { name: tag, color: DEFAULT_TAG_COLOR });
+        }
+    }
+    palette
+}
+
+pub fn set_palette(palette: Vec<PaletteTag>) {
+    *TAG_PALETTE.lock().unwrap() = palette;
+    save_palette();
+}
+
+// renames the tag on every sound as well
+pub fn rename_tag(old: &str, new: &str) {
+    let mut palette = TAG_PALETTE.lock().unwrap();
+    if palette.iter().any(|tag| tag.name == new) {
+        palette.retain(|tag| tag.name != old);
+    } else if let Some(tag) = palette.iter_mut().find(|tag| tag.name == old) {
+        tag.name = new.to_string();
+    }
+    drop(palette);
+    save_palette();
+
+    let mut tags = TAGS.lock().unwrap();
+    for sound_tags in tags.values_mut() {
+        if let Some(index) = sound_tags.iter().position(|t| t == old) {
+            if sound_tags.iter().any(|t| t == new) {
+                sound_tags.remove(index);
+            } else {
+                sound_tags[index] = new.to_string();
+            }
+        }
+    }
+    drop(tags);
+    tags_changed();
+}
+
+// removes the tag from the palette and every sound
+pub fn delete_tag(name: &str) {
+    TAG_PALETTE.lock().unwrap().retain(|tag| tag.name != name);
+    save_palette();
+
+    let mut tags = TAGS.lock().unwrap();
+    for sound_tags in tags.values_mut() {
+        sound_tags.retain(|t| t != name);
+    }
+    tags.retain(|_, sound_tags| !sound_tags.is_empty());
+    drop(tags);
+    tags_changed();
+}
@@ -0,0 +1,93 @@
+use eframe::{egui::{Color32, Rounding, Stroke, Visuals}, epaint::Shadow};
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use crate::{locale::t, settings::SETTINGS};
+
+// taken from geometry dash's menus
+const GD_BACKGROUND: Color32 = Color32::from_rgb(22, 26, 52);
+const GD_PANEL: Color32 = Color32::from_rgb(30, 36, 70);
+const GD_WINDOW: Color32 = Color32::from_rgb(38, 46, 88);
+const GD_BUTTON: Color32 = Color32::from_rgb(196, 112, 16);
+const GD_BUTTON_HOVERED: Color32 = Color32::from_rgb(235, 148, 20);
+const GD_BUTTON_ACTIVE: Color32 = Color32::from_rgb(255, 192, 40);
+const GD_TEXT: Color32 = Color32::from_rgb(250, 244, 230);
+const GD_GOLD: Color32 = Color32::from_rgb(255, 204, 48);
+const GD_GREEN: Color32 = Color32::from_rgb(96, 222, 64);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+pub enum AppTheme {
+    #[default]
+    Dark,
+    Light,
+    Gd,
+}
+
+impl AppTheme {
+    pub fn name(self) -> &'static str {
+        match self {
+            AppTheme::Dark => t!("theme.dark"),
+            AppTheme::Light => t!("theme.light"),
+            AppTheme::Gd => t!("theme.gd"),
+        }
+    }
+
+    pub fn visuals(self) -> Visuals {
+        match self {
+            AppTheme::Dark => Visuals::dark(),
+            AppTheme::Light => Visuals::light(),
+            AppTheme::Gd => gd_visuals(),
+        }
+    }
+
+    // sound names are only colored by download status when the theme has a color for it
+    pub fn downloaded_color(self) -> Option<Color32> {
+        match self {
+            AppTheme::Gd => Some(GD_GREEN),
+            _ => None,
+        }
+    }
+}
+
+fn gd_visuals() -> Visuals {
+    let mut visuals = Visuals::dark();
+
+    visuals.panel_fill = GD_PANEL;
+    visuals.window_fill = GD_WINDOW;
+    visuals.window_stroke = Stroke::new(1.0, GD_GOLD.gamma_multiply(0.5));
+    visuals.window_rounding = Rounding::same(8.0);
+    visuals.window_shadow = Shadow::small_dark();
+    visuals.extreme_bg_color = GD_BACKGROUND;
+    visuals.faint_bg_color = GD_PANEL.gamma_multiply(1.2);
+    visuals.code_bg_color = GD_BACKGROUND;
+    visuals.hyperlink_color = GD_GOLD;
+    visuals.warn_fg_color = GD_BUTTON_HOVERED;
+    visuals.override_text_color = Some(GD_TEXT);
+
+    visuals.selection.bg_fill = GD_BUTTON.gamma_multiply(0.8);
+    visuals.selection.stroke = Stroke::new(1.0, GD_GOLD);
+
+    let widgets = &mut visuals.widgets;
+    widgets.noninteractive.bg_fill = GD_PANEL;
+    widgets.noninteractive.weak_bg_fill = GD_PANEL;
+    widgets.noninteractive.bg_stroke = Stroke::new(1.0, GD_WINDOW.gamma_multiply(1.5));
+    for (state, fill) in [
+        (&mut widgets.inactive, GD_BUTTON),
+        (&mut widgets.hovered, GD_BUTTON_HOVERED),
+        (&mut widgets.active, GD_BUTTON_ACTIVE),
+        (&mut widgets.open, GD_BUTTON_HOVERED),
+    ] {
+        state.bg_fill = fill;
+        state.weak_bg_fill = fill;
+        state.fg_stroke = Stroke::new(1.0, GD_TEXT);
+        state.rounding = Rounding::same(4.0);
+    }
+    widgets.hovered.bg_stroke = Stroke::new(1.0, GD_GOLD);
+    widgets.active.bg_stroke = Stroke::new(1.0, GD_TEXT);
+
+    visuals
+}
+
+pub fn current_theme() -> AppTheme {
+    SETTINGS.lock().unwrap().theme
+}
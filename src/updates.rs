@@ -0,0 +1,51 @@
+use std::{sync::{Arc, Mutex}, thread::{spawn, JoinHandle}, time::{SystemTime, UNIX_EPOCH}};
+
+use lazy_static::lazy_static;
+use semver::Version;
+
+use crate::{requests::get_latest_release, settings::{get_settings, is_offline, save, SETTINGS}};
+
+pub const UPDATE_CHECK_INTERVAL: u64 = 60 * 60 * 24; // once a day
+
+lazy_static!{
+    // (version, url)
+    pub static ref AVAILABLE_UPDATE: Arc<Mutex<Option<(Version, String)>>> = Default::default();
+}
+
+pub fn check_for_updates() -> JoinHandle<()> {
+    spawn(|| {
+        let settings = get_settings();
+
+        if !settings.check_for_updates || is_offline() {
+            return;
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let mut latest_release = settings.latest_release;
+
+        if latest_release.is_none() || now >= settings.last_update_check + UPDATE_CHECK_INTERVAL {
+            // fail silently when offline and keep the cached release
+            if let Some(release) = get_latest_release() {
+                let mut settings = SETTINGS.lock().unwrap();
+                settings.latest_release = Some(release.clone());
+                settings.last_update_check = now;
+                drop(settings);
+                save();
+                latest_release = Some(release);
+            }
+        }
+
+        if let Some((tag, url)) = latest_release {
+            let current = Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
+            if let Ok(latest) = Version::parse(tag.trim_start_matches('v')) {
+                if latest > current {
+                    *AVAILABLE_UPDATE.lock().unwrap() = Some((latest, url));
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,74 @@
+use std::{path::PathBuf, sync::{Arc, Mutex}, fs};
+
+use eframe::epaint::ahash::HashMap;
+use lazy_static::lazy_static;
+
+use crate::{util::GD_FOLDER, encoding::{full_decode, full_encode}};
+
+pub const USED_CHARACTER: char = '🎮';
+
+lazy_static!{
+    pub static ref USAGE_FILE: PathBuf = GD_FOLDER.join("gdsfx_usage.dat");
+    // sound id -> names of the levels it's used in
+    pub static ref USED_IN_LEVELS: Arc<Mutex<HashMap<i64, Vec<String>>>> = Arc::new(Mutex::new(read_file()));
+}
+
+pub fn read_file() -> HashMap<i64, Vec<String>> {
+    if USAGE_FILE.exists() {
+        let data = fs::read(USAGE_FILE.as_path()).unwrap();
+        let data = full_decode(&data);
+        serde_json::from_slice(&data).unwrap_or_default()
+    } else {
+        HashMap::default()
+    }
+}
+
+pub fn save() {
+    let string = serde_json::to_string(&*USED_IN_LEVELS.lock().unwrap()).unwrap();
+    let data = full_encode(string.as_bytes());
+    fs::write(USAGE_FILE.as_path(), data).unwrap();
+}
+
+pub fn get_levels(id: i64) -> Vec<String> {
+    USED_IN_LEVELS.lock().unwrap().get(&id).cloned().unwrap_or_default()
+}
+
+pub fn is_used(id: i64) -> bool {
+    USED_IN_LEVELS.lock().unwrap().contains_key(&id)
+}
+
+pub fn mark_used(id: i64, level: &str) {
+    let mut usage = USED_IN_LEVELS.lock().unwrap();
+    let levels = usage.entry(id).or_default();
+    if !levels.iter().any(|l| l == level) {
+        levels.push(level.to_string());
+    }
+    drop(usage);
+    save();
+}
+
+pub fn unmark_used(id: i64, level: &str) {
+    let mut usage = USED_IN_LEVELS.lock().unwrap();
+    if let Some(levels) = usage.get_mut(&id) {
+        levels.retain(|l| l != level);
+        if levels.is_empty() {
+            usage.remove(&id);
+        }
+    }
+    drop(usage);
+    save();
+}
+
+// (unique used sounds, most referenced level with its sound count)
+pub fn usage_stats() -> (usize, Option<(String, usize)>) {
+    let usage = USED_IN_LEVELS.lock().unwrap();
+    let mut level_counts: HashMap<&str, usize> = HashMap::default();
+    for level in usage.values().flatten() {
+        *level_counts.entry(level).or_default() += 1;
+    }
+    let most_referenced = level_counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(level, count)| (level.to_string(), count));
+    (usage.len(), most_referenced)
+}
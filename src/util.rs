@@ -1,6 +1,8 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
 use std::path::PathBuf;
 use std::env;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 use eframe::epaint::ahash::HashMap;
 use eframe::epaint::mutex::Mutex;
 use lazy_static::lazy_static;
@@ -13,7 +15,12 @@ pub const TOTAL_HEIGHT: f32 = 600.0;
 
 lazy_static!{
     pub static ref GD_FOLDER: PathBuf = {
-        if cfg!(target_os = "windows") {
+        if cfg!(test) {
+            // tests never touch the real gd folder
+            let folder = env::temp_dir().join(format!("gd_sfx_test_{}", std::process::id()));
+            fs::create_dir_all(&folder).unwrap();
+            folder
+        } else if cfg!(target_os = "windows") {
             PathBuf::from(env::var("localappdata").expect("No local app data"))
                 .join("GeometryDash")
         } else if cfg!(target_os = "macos") {
@@ -31,6 +38,41 @@ lazy_static!{
     pub static ref SFX_LIBRARY_FILE: PathBuf = GD_FOLDER.join("sfxlibrary.dat");
 
     pub static ref LOCAL_SFX_LIBRARY: Arc<Mutex<HashMap<i64, Vec<u8>>>> = Default::default();
+
+    pub static ref INSTANCE_LOCK_FILE: PathBuf = GD_FOLDER.join("gd_sfx.lock");
+    // kept open for the whole run, the os releases the lock when the process exits
+    static ref INSTANCE_LOCK: Mutex<Option<File>> = Default::default();
+}
+
+// set on exit so background work doesn't write files anymore
+pub static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+pub const PARTIAL_DOWNLOAD_EXTENSION: &str = "part";
+
+// false if another instance already holds the lock
+pub fn acquire_instance_lock() -> bool {
+    let Ok(file) = OpenOptions::new().create(true).truncate(false).write(true).open(INSTANCE_LOCK_FILE.as_path()) else {
+        return true; // can't tell, don't bother the user
+    };
+    match file.try_lock() {
+        Ok(()) => {
+            *INSTANCE_LOCK.lock() = Some(file);
+            true
+        }
+        Err(TryLockError::WouldBlock) => false,
+        Err(TryLockError::Error(_)) => true,
+    }
+}
+
+// (sounds, bytes) of the sound data kept in memory
+pub fn sound_cache_size() -> (usize, usize) {
+    let cache = LOCAL_SFX_LIBRARY.lock();
+    (cache.len(), cache.values().map(Vec::len).sum())
+}
+
+// imported sounds only live in the cache, everything else can be read or downloaded again
+pub fn clear_sound_cache() {
+    LOCAL_SFX_LIBRARY.lock().retain(|id, _| *id < 0);
 }
 
 pub fn hide_console_window() {
@@ -45,3 +87,20 @@ pub fn stringify_duration(duration: i64) -> String {
     centiseconds.insert(centiseconds.len() - 2, '.');
     centiseconds
 }
+
+// `00000000  4f 67 67 53 00 02 ...  |OggS..|`, 16 bytes per line
+pub fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex = chunk.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(" ");
+            let ascii = chunk
+                .iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect::<String>();
+            format!("{:08x}  {hex:<47}  |{ascii}|", i * 16)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}